@@ -1,3 +1,7 @@
 fn main(){
-    println!("cargo:rustc-link-arg=resources/resources.res");
-}
\ No newline at end of file
+    println!("cargo:rerun-if-changed=build.rs"); //Without any rerun-if-changed cargo reruns this whenever any file in the package changes
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("windows"){ //The compiled resource file gives the Windows executable its icon, other platforms take the icon from the window (see main.rs) or a .desktop file
+        println!("cargo:rerun-if-changed=resources/resources.res"); //Only here, the file isn't in the repo and cargo treats a missing path as always changed
+        println!("cargo:rustc-link-arg=resources/resources.res");
+    }
+}
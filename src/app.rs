@@ -1,32 +1,82 @@
-use std::{collections::HashMap, process::Command, time::{Duration, Instant}};
-use file_icon_provider::get_file_icon;
-use image::{DynamicImage, RgbaImage};
-use egui::{Color32, ColorImage, FontFamily, FontId, Id, Key, RichText, TextStyle, TextureHandle, TextureOptions, ThemePreference, Vec2, Window, load::SizedTexture};
+use std::{collections::HashMap, path::Path};
+use egui::{Color32, FontFamily, FontId, Id, Key, RichText, TextStyle, TextureHandle, TextureOptions, ThemePreference, Vec2, Window, load::SizedTexture};
 use rfd::FileDialog;
-use sysinfo::{Pid, Process, ProcessRefreshKind, RefreshKind, System};
+
+use crate::artwork::{ArtworkFolder, artwork_path, import_artwork, remove_artwork};
+use crate::library::{AppEntry, AppId, ArtworkKind, Library, get_executable_name};
+use crate::dates::{format_timestamp, now_millis};
+#[cfg(target_os = "linux")]
+use crate::desktop::{DesktopEntry, scan_desktop_entries};
+use crate::history::{History, SessionRecord};
+use crate::icons::{IconLoader, missing_icon};
+#[cfg(target_os = "linux")]
+use crate::icons::find_theme_icon;
+use crate::textures::{TextureCache, TextureTier};
+use crate::idle::is_input_idle_supported;
+use crate::launch::{LaunchOptions, ProfileId, describe_launch_error, open_app, open_url, working_dir};
+use crate::migration;
+use crate::notifications::Notifications;
+use crate::search::{AppFilter, GroupFilter, InstalledFilter, PlayedFilter, SortMode, sort_entries};
+use crate::settings::Settings;
+use crate::stats::{self, Period};
+use crate::steam::{SteamGame, find_steam_dir, run_game_url, scan_steam_games};
+use crate::tracker::{Session, Tracker, TrackerEvent, read_checkpoints};
+
+pub const APP_NAME : &str = "Catapult"; //Also decides where eframe keeps the save file, see eframe::storage_dir
 
 
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct CatapultApp {
+    #[serde(default)] //Not the struct's default, a save that's missing this predates versioning and must be treated as version 0
+    schema_version : u32, //Which migration::SCHEMA_VERSION wrote this save
+
 
+    library : Library, //Every app the user has added, along with its name, play time and groups
+    central_view : CentralView, //What the central panel is showing
+    settings : Settings,
+    history : History, //Every play session, used for the per app history list
+    selected_app : Option<AppId>, //The app shown in the central panel
+    all_apps_sort : SortMode, //How the "ALL Apps" list is ordered
+    group_sorts : HashMap<String, SortMode>, //How each group's list is ordered, groups that aren't in here are sorted by name
     #[serde(skip)]
-    delta_time : Duration, //The time since last frame
+    app_texture_handles : TextureCache, //Cache the texture handles for the app icons and artwork, so we don't have to reload them every frame (big performance increase trust me, i wish i could serialize texture handles but alas)
     #[serde(skip)]
-    last_instant : Instant, //The time of the last frame, used to calculate delta_time
+    icon_loader : Option<IconLoader>, //Extracts executable icons and decodes artwork in the background, only None until new() has started it
+    #[serde(skip)]
+    missing_icon : Option<TextureHandle>, //Shown while an icon or image is still being loaded
 
-    app_folders : HashMap<String,Vec<String>>, //A hashmap of folder names to the apps in those folders, used to organize apps into groups
-    app_folder_names : Vec<String>, //A vector of folder names, used to maintain the order of the folders in the UI (since hashmaps don't maintain order)
-    apps : Vec<String>, //A vector of executable paths for all the apps the user has added, used to display the apps in the "All Apps" default group
-    selected_app : String,
-    apps_aliases : HashMap<String, String>, //A hashmap of executable paths to their corresponding app names, used to allow the user to specify a custom name for each app instead of just using the executable name. If an app doesn't have a custom name, the executable name will be used as a default (see get_executable_name function)
-    app_play_time : HashMap<String, u64>, //A hashmap of executable paths to the total time (in milliseconds)
+    #[serde(skip)]
+    is_editing_app : bool, //Whether the "Edit App" window should be open for the currently selected app
 
     #[serde(skip)]
-    app_texture_handles : HashMap<String, TextureHandle>, //Cache the texture handles for the app icons, so we don't have to reload them every frame (big performance increase trust me, i wish i could serialize texture handles but alas)
+    editing_profile : usize, //Which of the selected app's profiles the "Edit App" window is showing
 
     #[serde(skip)]
-    is_editing_app : bool, //Whether the "Edit App" window should be open for the currently selected app
+    is_showing_history : bool, //Whether the central panel lists the selected app's past sessions
+    #[serde(skip)]
+    editing_session : Option<u64>, //The session the "Edit Session" window is open for
+    #[serde(skip)]
+    current_session_minutes : u64,
+
+    #[serde(skip)]
+    stats_period : Period, //Whether the Statistics chart shows days, weeks or months
+
+    #[serde(skip)]
+    is_editing_settings : bool, //Whether the "Settings" window is open
+
+    #[cfg(target_os = "linux")]
+    #[serde(skip)]
+    desktop_import : Option<Vec<(DesktopEntry, bool)>>, //The entries listed in the "Import from Desktop Entries" window and whether each one is ticked, None while it's closed
+
+    #[serde(skip)]
+    steam_import : Option<Vec<(SteamGame, bool)>>, //The games listed in the "Import Steam Library" window and whether each one is ticked, None while it's closed
+
+    #[serde(skip)]
+    app_filter : AppFilter, //The search box and filters above the app lists
+
+    #[serde(skip)]
+    artwork_folder : ArtworkFolder, //The images in the artwork folder from the settings, scanned on start and whenever the setting changes
 
     #[serde(skip)]
     is_app_selected : bool, //Whether the "Add App" window should be open for the currently selected app
@@ -38,38 +88,64 @@ pub struct CatapultApp {
     current_path : String,
     #[serde(skip)]
     current_folder_name : String,
-
     #[serde(skip)]
-    sys : System,
+    current_tags : String, //The selected app's tags as typed into the "Edit App" window, comma separated
 
     #[serde(skip)]
-    running_apps : HashMap<String,usize>, //Stores the PID of all running apps, so we can track it and update play time
+    running_apps : HashMap<AppId,u64>, //The history record of each app the tracker thread is tracking
     #[serde(skip)]
-    app_to_remove : String,
+    tracker : Option<Tracker>, //Only None until new() has started the tracker thread
+    #[serde(skip)]
+    tracked_library : Library, //The library as it was last sent to the tracker, which looks for its apps among the running processes
+
+    #[serde(skip)]
+    notifications : Notifications, //Errors shown to the user in the corner of the window
 
 }
 
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+enum CentralView {
+    App, //The selected app, with its launch button and history
+    Grid, //Every app as a tile with its cover, like a shelf of games
+    Statistics,
+}
+
 impl Default for CatapultApp {
     fn default() -> Self {
         Self {
-            delta_time : Duration::new(0, 0),
-            last_instant : Instant::now(),
-            app_folders : HashMap::new(),
-            app_folder_names : Vec::new(),
-            apps : Vec::new(),
-            apps_aliases : HashMap::new(),
-            app_texture_handles : HashMap::new(),
-            app_play_time : HashMap::new(),
-            selected_app : "".to_string(),
+            schema_version : migration::SCHEMA_VERSION,
+            library : Library::new(),
+            central_view : CentralView::App,
+            settings : Settings::default(),
+            history : History::default(),
+            app_texture_handles : TextureCache::default(),
+            icon_loader : None,
+            missing_icon : None,
+            selected_app : None,
+            all_apps_sort : SortMode::Name,
+            group_sorts : HashMap::new(),
             is_editing_app : false,
+            editing_profile : 0,
+            is_showing_history : false,
+            stats_period : Period::Day,
+            editing_session : None,
+            current_session_minutes : 0,
+            is_editing_settings : false,
+            #[cfg(target_os = "linux")]
+            desktop_import : None,
+            steam_import : None,
+            app_filter : AppFilter::default(),
+            artwork_folder : ArtworkFolder::default(),
             is_app_selected : false,
             is_folder_created : false,
             current_app_name : "".to_string(),
             current_path : "".to_string(),
             current_folder_name : "".to_string(),
-            sys : System::new_with_specifics(RefreshKind::nothing().with_processes(ProcessRefreshKind::everything())),
+            current_tags : "".to_string(),
             running_apps : HashMap::new(),
-            app_to_remove : "".to_string(),
+            tracker : None,
+            tracked_library : Library::new(),
+            notifications : Notifications::default(),
         }
     }
 }
@@ -77,10 +153,662 @@ impl Default for CatapultApp {
 impl CatapultApp {
     /// Called once before the first frame.
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        if let Some(storage) = cc.storage {
-            eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default()
+        let mut app : CatapultApp = if let Some(storage) = cc.storage {
+            let version = migration::saved_version(storage);
+            let loaded : Option<CatapultApp> = if version == 0 { None } else { eframe::get_value(storage, eframe::APP_KEY) }; //Saves from before versioning are keyed by path and have to go through the migration, even if they'd happen to deserialize
+            let backup = if version != migration::SCHEMA_VERSION || loaded.is_none() { Some(migration::backup_save(storage, APP_NAME, version)) } else { None }; //Keep a copy of the old save before anything touches it, including saves we couldn't read at all and saves from a newer version
+            let is_unreadable = version != 0 && loaded.is_none();
+            let mut app = loaded.unwrap_or_default();
+            if version < migration::SCHEMA_VERSION{
+                migration::migrate(&mut app.library, &mut app.history, storage, version);
+            }
+            let backup_note = match &backup {
+                Some(Ok(Some(path))) => format!("a copy of it was kept at {}", path.display()),
+                Some(Err(error)) => format!("and it couldn't be backed up: {}", error),
+                _ => "and there was nothing to back up".to_string(),
+            };
+            if is_unreadable{ //Starting over with defaults, which replace the save the next time we save
+                app.notifications.error(format!("Couldn't read the save, so Catapult started with an empty library. It will be replaced the next time Catapult saves, {}.", backup_note));
+            } else if version > migration::SCHEMA_VERSION{ //Anything only the newer version knows about is dropped the next time we save
+                app.notifications.error(format!("This save was written by a newer version of Catapult (schema {}, this version uses {}). Anything this version doesn't know about will be lost when it saves, {}.", version, migration::SCHEMA_VERSION, backup_note));
+            } else if let Some(Err(error)) = &backup{ //The migration still runs, there's no going back to the old version's format either way
+                app.notifications.error(format!("Couldn't back up the save before updating it from schema {} to {}: {}", version, migration::SCHEMA_VERSION, error));
+            }
+            app.schema_version = migration::SCHEMA_VERSION;
+            app
         } else {
             Default::default()
+        };
+        app.recover_checkpoints();
+        app.artwork_folder = ArtworkFolder::scan(Path::new(&app.settings.artwork_folder));
+        let mut icon_loader = IconLoader::spawn(cc.egui_ctx.clone());
+        for entry in app.library.entries(){ //Start loading every icon for the lists now rather than when each one first comes on screen
+            icon_loader.request(&entry.path, TextureTier::Small);
+        }
+        app.icon_loader = Some(icon_loader);
+        app.tracker = Some(Tracker::spawn(cc.egui_ctx.clone(), app.settings.clone(), checkpoint_path()));
+        app.send_library_to_tracker(); //Its first scan picks back up any games that were still running when Catapult was closed
+        app
+    }
+
+    fn recover_checkpoints(&mut self){ //Counts play time from sessions that were checkpointed but never made it into a save, because Catapult crashed or was closed while they were running
+        let Some(path) = checkpoint_path() else { return };
+        for checkpoint in read_checkpoints(&path){
+            let Some(record) = self.history.get(checkpoint.record) else { continue };
+            if record.app == checkpoint.app && checkpoint.last_checkpoint > record.end{
+                let elapsed = checkpoint.last_checkpoint - record.end;
+                let idle = checkpoint.idle.min(elapsed);
+                self.library.add_play_time(checkpoint.app, checkpoint.profile, elapsed - idle);
+                self.history.add_time(checkpoint.record, elapsed - idle, idle, checkpoint.last_checkpoint);
+            }
+        }
+    }
+
+    fn send_library_to_tracker(&mut self){ //Lets the tracker look for apps that were started without us, only when the library has changed since it was last sent
+        if self.tracked_library != self.library && let Some(tracker) = self.tracker.as_ref(){
+            tracker.set_library(self.library.clone());
+            self.tracked_library = self.library.clone();
+        }
+    }
+
+    fn attach_session(&mut self, app : AppId, profile : ProfileId, start : u64){ //Opens a history record for an app the tracker found running on its own
+        if self.library.get(app).is_none(){
+            if let Some(tracker) = self.tracker.as_ref(){
+                tracker.untrack(app); //Removed before the tracker heard about it
+            }
+            return;
+        }
+        if self.running_apps.contains_key(&app){
+            return; //We launched it at the same moment, the tracker drops its own session when ours arrives
+        }
+        let record = self.open_record(app, profile, start);
+        if let Some(tracker) = self.tracker.as_ref(){
+            tracker.set_record(app, record);
+        }
+    }
+
+    fn launch_app(&mut self, ctx : &egui::Context, entry : &AppEntry, profile_index : usize){ //Starts the app with one of its profiles and starts tracking its play time
+        if self.running_apps.contains_key(&entry.id){ //Only one session per app, the UI only listens to the newest one so the first would never be finished
+            return;
+        }
+        let Some(profile) = entry.profile(profile_index) else { return };
+        if entry.steam_app_id != 0 && profile.path.is_empty(){ //Steam has to start its own games, profiles with their own executable still run it directly
+            let url = run_game_url(entry.steam_app_id);
+            match open_url(&url){
+                Ok(()) => {
+                    let session = Session::waiting(entry.id, profile.id, &entry.tracked_process_name).with_tracked_dir(&entry.tracked_dir);
+                    self.start_session(session);
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+                }
+                Err(error) => self.notifications.error(format!("Couldn't open {}, is Steam installed? {}", url, error)),
+            }
+            return;
+        }
+        let path = entry.profile_path(profile);
+        match open_app(path, &profile.options){
+            Ok(child) => {
+                let session = Session::new(entry.id, profile.id, child.id() as usize, &entry.tracked_process_name).with_tracked_dir(&entry.tracked_dir).with_child(child);
+                self.start_session(session);
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            Err(error) => self.notifications.error(describe_launch_error(path, &error)), //Stay up so the user can see what went wrong
+        }
+    }
+
+    fn start_session(&mut self, mut session : Session){ //Opens the session's record in the history and hands it to the tracker thread
+        session.record = Some(self.open_record(session.app, session.profile, session.last_checkpoint));
+        if let Some(tracker) = self.tracker.as_ref(){
+            tracker.track(session);
+        }
+    }
+
+    fn open_record(&mut self, app : AppId, profile : ProfileId, start : u64) -> u64{ //Starts the session's record in the history and marks the app as running
+        let record = self.history.start(app, profile, start);
+        self.running_apps.insert(app, record);
+        record
+    }
+
+    fn delete_session(&mut self, id : u64){ //Deletes a session from the history along with the play time it added
+        if let Some(record) = self.history.get(id) && self.running_apps.get(&record.app) == Some(&id){ //Stop tracking it too, or the tracker keeps adding time to a session that's gone
+            self.untrack_app(record.app);
+        }
+        if let Some(record) = self.history.remove(id){
+            self.library.remove_play_time(record.app, record.profile_id, record.duration);
+        }
+    }
+
+    fn untrack_app(&mut self, app : AppId){ //Stops tracking the app's running session, any events already on their way for it are ignored (see update)
+        self.running_apps.remove(&app);
+        if let Some(tracker) = self.tracker.as_ref(){
+            tracker.untrack(app);
+        }
+    }
+
+    fn set_session_duration(&mut self, id : u64, duration : u64){ //Fixes up a session that was counted wrong, e.g. a game left running overnight
+        let Some(record) = self.history.get(id).cloned() else { return };
+        if let Some(old_duration) = self.history.set_duration(id, duration){
+            self.library.remove_play_time(record.app, record.profile_id, old_duration); //If the profile has been removed only the app's total gets fixed
+            self.library.add_play_time(record.app, record.profile_id, duration);
+        }
+    }
+
+    fn history_ui(&mut self, ui : &mut egui::Ui, entry : &AppEntry){ //The list of past sessions in the central panel
+        let sessions : Vec<SessionRecord> = self.history.for_app(entry.id).rev().cloned().collect();
+        if sessions.is_empty(){
+            ui.label("No sessions recorded yet");
+            return;
+        }
+        egui::ScrollArea::vertical()
+        .max_height(240.0)
+        .auto_shrink([false, true])
+        .show(ui, |ui|{
+            egui::Grid::new("History").striped(true).spacing([16.0, 8.0]).show(ui, |ui|{
+                ui.label("Started (UTC)");
+                ui.label("Played");
+                ui.label("Wall Time");
+                ui.label("Idle");
+                ui.label("Profile");
+                ui.label("Exit Code");
+                ui.end_row();
+                for session in sessions.iter(){
+                    ui.label(format_timestamp(session.start));
+                    ui.label(time_from_millis(session.duration));
+                    ui.label(time_from_millis(session.end.saturating_sub(session.start)));
+                    ui.label(time_from_millis(session.idle));
+                    ui.label(profile_name(entry, session.profile_id));
+                    let is_running = self.running_apps.get(&session.app).is_some_and(|record| *record == session.id);
+                    ui.label(if is_running { "Running".to_string() } else { session.exit_code.map(|code| code.to_string()).unwrap_or("-".to_string()) });
+                    if ui.button("Edit").clicked(){
+                        self.editing_session = Some(session.id);
+                        self.current_session_minutes = session.duration / 60000;
+                    }
+                    if ui.button("Delete").clicked(){
+                        self.delete_session(session.id);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    fn settings_ui(&mut self, ctx : &egui::Context){ //The "Settings" window, opened from the File menu
+        if !self.is_editing_settings{
+            return;
+        }
+        let old_settings = self.settings.clone();
+        Window::new("Settings").show(ctx, |ui|{
+            ui.horizontal(|ui|{
+                ui.label("Check running apps every");
+                ui.add(egui::DragValue::new(&mut self.settings.poll_interval_secs).range(1..=60).suffix(" seconds"));
+            });
+            ui.separator();
+            ui.label("Idle detection");
+            if is_input_idle_supported(){
+                ui.horizontal(|ui|{
+                    ui.checkbox(&mut self.settings.idle_input, "Pause after no keyboard or mouse input for");
+                    ui.add_enabled(self.settings.idle_input, egui::DragValue::new(&mut self.settings.idle_minutes).range(1..=120).suffix(" minutes"));
+                });
+                ui.label(RichText::new("Gamepad input isn't detected, leave this off if you play with a controller").weak());
+            }
+            ui.horizontal(|ui|{
+                ui.checkbox(&mut self.settings.idle_cpu, "Pause while the app uses less than");
+                ui.add_enabled(self.settings.idle_cpu, egui::DragValue::new(&mut self.settings.idle_cpu_percent).range(0.1..=50.0).speed(0.1).suffix("% CPU"));
+            });
+            ui.separator();
+            ui.label("Artwork folder");
+            ui.label(RichText::new("Images named after a game, like \"Hollow Knight.png\", are used as its cover. Add _hero, _logo or _icon to the name for the other kinds").weak());
+            ui.horizontal(|ui|{
+                ui.label(if self.settings.artwork_folder.is_empty() { "None" } else { &self.settings.artwork_folder });
+                if ui.button("Choose Folder").clicked() && let Some(folder) = FileDialog::new().pick_folder() && let Some(folder) = folder.to_str(){
+                    self.settings.artwork_folder = folder.to_string();
+                }
+                if !self.settings.artwork_folder.is_empty(){
+                    if ui.button("Rescan").clicked(){
+                        self.artwork_folder = ArtworkFolder::scan(Path::new(&self.settings.artwork_folder));
+                    }
+                    if ui.button("Clear").clicked(){
+                        self.settings.artwork_folder = "".to_string();
+                    }
+                }
+            });
+            if ui.button("Close").clicked() || ui.input(|i| i.key_pressed(Key::Escape)){
+                self.is_editing_settings = false;
+            }
+        });
+        if self.settings.artwork_folder != old_settings.artwork_folder{
+            self.artwork_folder = ArtworkFolder::scan(Path::new(&self.settings.artwork_folder));
+        }
+        if self.settings != old_settings && let Some(tracker) = self.tracker.as_ref(){
+            tracker.set_settings(self.settings.clone());
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn desktop_import_ui(&mut self, ctx : &egui::Context){ //The "Import from Desktop Entries" window, opened from the File menu
+        let Some(entries) = self.desktop_import.as_mut() else { return };
+        let mut is_closed = false;
+        let mut is_imported = false;
+        Window::new("Import from Desktop Entries").show(ctx, |ui|{
+            if entries.is_empty(){
+                ui.label("No desktop entries found");
+            } else {
+                ui.horizontal(|ui|{
+                    if ui.button("Select All").clicked(){
+                        entries.iter_mut().for_each(|(entry, is_selected)| *is_selected = !is_desktop_entry_added(&self.library, entry));
+                    }
+                    if ui.button("Select None").clicked(){
+                        entries.iter_mut().for_each(|(_, is_selected)| *is_selected = false);
+                    }
+                });
+            }
+            egui::ScrollArea::vertical()
+            .max_height(400.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui|{
+                egui::Grid::new("Desktop Entries").striped(true).spacing([16.0, 8.0]).show(ui, |ui|{
+                    for (entry, is_selected) in entries.iter_mut(){
+                        let is_added = is_desktop_entry_added(&self.library, entry);
+                        ui.add_enabled(!is_added, egui::Checkbox::new(is_selected, &entry.name));
+                        ui.label(if is_added { "Already added".to_string() } else { format!("{} {}", entry.program, entry.options.args.join(" ")) });
+                        ui.label(entry.groups.join(", "));
+                        ui.end_row();
+                    }
+                });
+            });
+            ui.horizontal(|ui|{
+                if ui.button("Import Selected").clicked(){
+                    is_imported = true;
+                }
+                if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(Key::Escape)){
+                    is_closed = true;
+                }
+            });
+        });
+        if is_imported{
+            let selected : Vec<DesktopEntry> = entries.iter().filter(|(_, is_selected)| *is_selected).map(|(entry, _)| entry.clone()).collect();
+            self.import_desktop_entries(selected);
+            is_closed = true;
+        }
+        if is_closed{
+            self.desktop_import = None;
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn import_desktop_entries(&mut self, entries : Vec<DesktopEntry>){ //Adds each entry with its arguments, groups and icon. Steam shortcuts become Steam games, launched and tracked like ones from "Import Steam Library"
+        let steam_games = if entries.iter().any(|entry| entry.steam_app_id.is_some()) { find_steam_dir().map(|steam_dir| scan_steam_games(&steam_dir)).unwrap_or_default() } else { Vec::new() };
+        for entry in entries{
+            if is_desktop_entry_added(&self.library, &entry){
+                continue;
+            }
+            let steam_game = entry.steam_app_id.and_then(|app_id| steam_games.iter().find(|game| game.app_id == app_id));
+            let install_dir = steam_game.map(|game| game.install_dir.to_string_lossy().to_string());
+            let id = self.library.add_new(install_dir.as_deref().unwrap_or(&entry.program), &entry.name); //Always a new entry, many entries run the same program (steam, flatpak, python...) with different arguments
+            match (steam_game, install_dir){
+                (Some(game), Some(install_dir)) => {
+                    self.library.set_steam_app_id(id, game.app_id);
+                    self.library.set_tracked_dir(id, &install_dir); //Without it the session only follows what Steam starts, which isn't a child of ours
+                }
+                _ if entry.steam_app_id.is_some() => { //Without the folder there's nothing to recognise the game by, so it stays a plain shortcut that runs steam
+                    self.notifications.error(format!("Couldn't find where Steam installed {}, so its play time can't be tracked until its Tracked Folder is set in Edit App.", entry.name));
+                }
+                _ => {}
+            }
+            if let Some(mut profile) = self.library.get(id).and_then(|added| added.profile(0)).cloned(){
+                profile.options = entry.options;
+                self.library.set_profile(id, 0, profile);
+            }
+            for group in entry.groups.iter(){
+                self.library.create_group(group);
+                self.library.add_to_group(id, group);
+            }
+            if !entry.icon.is_empty() && let Some(icon) = find_theme_icon(&entry.icon, 256){ //Looked up only now, for the entries that were picked, the icon theme is slow to search
+                match import_artwork(&icon, id, ArtworkKind::Icon){
+                    Ok(file_name) => self.replace_artwork(id, ArtworkKind::Icon, &file_name),
+                    Err(error) => self.notifications.error(format!("Couldn't use {} as the icon for {}: {}", icon.display(), entry.name, error)),
+                }
+            }
+        }
+    }
+
+    fn steam_import_ui(&mut self, ctx : &egui::Context){ //The "Import Steam Library" window, opened from the File menu
+        let Some(games) = self.steam_import.as_mut() else { return };
+        let mut is_closed = false;
+        let mut is_imported = false;
+        Window::new("Import Steam Library").show(ctx, |ui|{
+            if games.is_empty(){
+                ui.label("No installed Steam games found");
+            } else {
+                ui.horizontal(|ui|{
+                    if ui.button("Select All").clicked(){
+                        games.iter_mut().for_each(|(game, is_selected)| *is_selected = !is_steam_game_added(&self.library, game));
+                    }
+                    if ui.button("Select None").clicked(){
+                        games.iter_mut().for_each(|(_, is_selected)| *is_selected = false);
+                    }
+                });
+            }
+            egui::ScrollArea::vertical()
+            .max_height(400.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui|{
+                egui::Grid::new("Steam Games").striped(true).spacing([16.0, 8.0]).show(ui, |ui|{
+                    for (game, is_selected) in games.iter_mut(){
+                        let is_added = is_steam_game_added(&self.library, game);
+                        ui.add_enabled(!is_added, egui::Checkbox::new(is_selected, &game.name));
+                        ui.label(if is_added { "Already added".to_string() } else { game.install_dir.display().to_string() });
+                        ui.end_row();
+                    }
+                });
+            });
+            ui.horizontal(|ui|{
+                if ui.button("Import Selected").clicked(){
+                    is_imported = true;
+                }
+                if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(Key::Escape)){
+                    is_closed = true;
+                }
+            });
+        });
+        if is_imported{
+            let selected : Vec<SteamGame> = games.iter().filter(|(_, is_selected)| *is_selected).map(|(game, _)| game.clone()).collect();
+            self.import_steam_games(selected);
+            is_closed = true;
+        }
+        if is_closed{
+            self.steam_import = None;
+        }
+    }
+
+    fn open_steam_import(&mut self){ //Scans the Steam install, asking where it is if it isn't in the usual place
+        let steam_dir = find_steam_dir().or_else(|| FileDialog::new().set_title("Where is Steam installed?").set_directory(default_dialog_dir()).pick_folder());
+        if let Some(steam_dir) = steam_dir{
+            self.steam_import = Some(scan_steam_games(&steam_dir).into_iter().map(|game| (game, false)).collect());
+        }
+    }
+
+    fn import_steam_games(&mut self, games : Vec<SteamGame>){ //Adds each game, launched through Steam and tracked by its install folder, with the artwork Steam has for it
+        for game in games{
+            let install_dir = game.install_dir.to_string_lossy().to_string(); //Games don't have one executable we can rely on, so the folder stands in for it
+            if is_steam_game_added(&self.library, &game){
+                continue;
+            }
+            let id = self.library.add_new(&install_dir, &game.name);
+            self.library.set_steam_app_id(id, game.app_id);
+            self.library.set_tracked_dir(id, &install_dir);
+            self.library.create_group("Steam");
+            self.library.add_to_group(id, "Steam");
+            for (kind, image) in game.artwork{
+                match import_artwork(&image, id, kind){
+                    Ok(file_name) => self.replace_artwork(id, kind, &file_name),
+                    Err(error) => self.notifications.error(format!("Couldn't use {} as the {} for {}: {}", image.display(), kind.label().to_lowercase(), game.name, error)),
+                }
+            }
+        }
+    }
+
+    fn search_ui(&mut self, ui : &mut egui::Ui){ //The search box and filters above the app lists
+        ui.horizontal(|ui|{
+            ui.add(egui::TextEdit::singleline(&mut self.app_filter.query).hint_text("Search by name, executable or tag").min_size(Vec2 { x: 400.0, y: 0.0 }));
+            if self.app_filter.is_active() && ui.button("Clear").clicked(){
+                self.app_filter = AppFilter::default();
+            }
+        });
+        ui.horizontal(|ui|{
+            egui::ComboBox::from_id_salt("Installed Filter")
+            .selected_text(match self.app_filter.installed { InstalledFilter::Any => "Installed or Missing", InstalledFilter::Installed => "Installed", InstalledFilter::Missing => "Missing" })
+            .show_ui(ui, |ui|{
+                ui.selectable_value(&mut self.app_filter.installed, InstalledFilter::Any, "Installed or Missing");
+                ui.selectable_value(&mut self.app_filter.installed, InstalledFilter::Installed, "Installed");
+                ui.selectable_value(&mut self.app_filter.installed, InstalledFilter::Missing, "Missing");
+            });
+            egui::ComboBox::from_id_salt("Group Filter")
+            .selected_text(match self.app_filter.grouping { GroupFilter::Any => "Any Group", GroupFilter::InGroup => "In a Group", GroupFilter::Ungrouped => "Ungrouped" })
+            .show_ui(ui, |ui|{
+                ui.selectable_value(&mut self.app_filter.grouping, GroupFilter::Any, "Any Group");
+                ui.selectable_value(&mut self.app_filter.grouping, GroupFilter::InGroup, "In a Group");
+                ui.selectable_value(&mut self.app_filter.grouping, GroupFilter::Ungrouped, "Ungrouped");
+            });
+            egui::ComboBox::from_id_salt("Played Filter")
+            .selected_text(match self.app_filter.played { PlayedFilter::Any => "Played or Not", PlayedFilter::Played => "Played", PlayedFilter::NeverPlayed => "Never Played" })
+            .show_ui(ui, |ui|{
+                ui.selectable_value(&mut self.app_filter.played, PlayedFilter::Any, "Played or Not");
+                ui.selectable_value(&mut self.app_filter.played, PlayedFilter::Played, "Played");
+                ui.selectable_value(&mut self.app_filter.played, PlayedFilter::NeverPlayed, "Never Played");
+            });
+        });
+    }
+
+    fn icon_texture(&mut self, ctx : &egui::Context, entry : &AppEntry, tier : TextureTier) -> TextureHandle { //The app's custom icon, or the executable's own one, from the cache, loading it the first time it's needed
+        if let Some(handle) = self.artwork_texture(ctx, entry, ArtworkKind::Icon, tier){
+            return handle;
+        }
+        self.exe_icon_texture(ctx, &entry.path, tier)
+    }
+
+    fn exe_icon_texture(&mut self, ctx : &egui::Context, path : &str, tier : TextureTier) -> TextureHandle { //The executable's own icon. While the icon loader is still working on it, another size of it or a placeholder
+        if let Some(handle) = self.app_texture_handles.get(path, tier){
+            return handle;
+        }
+        if let Some(icon_loader) = self.icon_loader.as_mut(){
+            icon_loader.request(path, tier);
+        }
+        if let Some(handle) = self.app_texture_handles.get_nearest(path, tier){
+            return handle;
+        }
+        self.placeholder_texture(ctx)
+    }
+
+    fn placeholder_texture(&mut self, ctx : &egui::Context) -> TextureHandle { //The missing_icon, for images that are still loading
+        self.missing_icon.get_or_insert_with(|| ctx.load_texture("missing_icon", missing_icon(), TextureOptions::LINEAR)).clone()
+    }
+
+    fn is_placeholder(&self, handle : &TextureHandle) -> bool {
+        self.missing_icon.as_ref().is_some_and(|missing_icon| missing_icon.id() == handle.id())
+    }
+
+    fn cover_texture(&mut self, ctx : &egui::Context, entry : &AppEntry, tier : TextureTier) -> TextureHandle { //The app's cover, falling back to its icon
+        match self.artwork_texture(ctx, entry, ArtworkKind::Cover, tier){
+            Some(handle) => handle,
+            None => self.icon_texture(ctx, entry, tier),
+        }
+    }
+
+    fn artwork_texture(&mut self, ctx : &egui::Context, entry : &AppEntry, kind : ArtworkKind, tier : TextureTier) -> Option<TextureHandle> { //One of the app's custom images from the cache, keyed by file name, or the placeholder while it loads. None if the app doesn't have one
+        let file_name = entry.artwork.get(kind);
+        if file_name.is_empty(){
+            return self.folder_artwork_texture(ctx, entry, kind, tier);
+        }
+        let path = artwork_path(file_name)?;
+        Some(self.image_texture(ctx, file_name, &path, tier))
+    }
+
+    fn folder_artwork_texture(&mut self, ctx : &egui::Context, entry : &AppEntry, kind : ArtworkKind, tier : TextureTier) -> Option<TextureHandle> { //An image from the artwork folder that matches the app's name, keyed by its path
+        let path = self.artwork_folder.find(entry, kind)?;
+        Some(self.image_texture(ctx, &path.to_string_lossy(), &path, tier))
+    }
+
+    fn image_texture(&mut self, ctx : &egui::Context, key : &str, path : &Path, tier : TextureTier) -> TextureHandle { //Like exe_icon_texture, for image files
+        if let Some(handle) = self.app_texture_handles.get(key, tier){
+            return handle;
+        }
+        if let Some(icon_loader) = self.icon_loader.as_mut(){
+            icon_loader.request_image(key, path, tier);
+        }
+        if let Some(handle) = self.app_texture_handles.get_nearest(key, tier){
+            return handle;
+        }
+        self.placeholder_texture(ctx)
+    }
+
+    fn forget_broken_image(&mut self, key : &str){ //For images the icon loader couldn't decode. Custom artwork is unset and images from the artwork folder stop being matched, so we don't keep trying
+        let mut is_artwork = false;
+        for entry in self.library.entries().to_vec(){
+            for kind in ArtworkKind::ALL.into_iter().filter(|kind| entry.artwork.get(*kind) == key){
+                self.notifications.error(format!("Couldn't load the {} image for {}, it has been reset", kind.label().to_lowercase(), entry.name));
+                self.library.set_artwork(entry.id, kind, "");
+                is_artwork = true;
+            }
+        }
+        if !is_artwork{
+            self.artwork_folder.forget(Path::new(key));
+        }
+    }
+
+    fn choose_artwork(&mut self, entry : &AppEntry, kind : ArtworkKind){ //Lets the user pick an image and copies it into the artwork directory
+        let Some(picked_path) = FileDialog::new().add_filter("Images", &["png", "jpg", "jpeg"]).pick_file() else { return };
+        match import_artwork(&picked_path, entry.id, kind){
+            Ok(file_name) => self.replace_artwork(entry.id, kind, &file_name),
+            Err(error) => self.notifications.error(format!("Couldn't use {} as the {}: {}", picked_path.display(), kind.label().to_lowercase(), error)),
+        }
+    }
+
+    fn replace_artwork(&mut self, id : AppId, kind : ArtworkKind, file_name : &str){ //Sets or (with an empty file name) clears a custom image, deleting the one it replaces
+        if let Some(old_file_name) = self.library.set_artwork(id, kind, file_name) && !old_file_name.is_empty(){
+            self.app_texture_handles.remove(&old_file_name);
+            remove_artwork(&old_file_name);
+        }
+    }
+
+    fn grid_ui(&mut self, ctx : &egui::Context, ui : &mut egui::Ui){ //The Library Grid screen in the central panel, every app that passes the search as a tile
+        let sorted = sort_entries(self.library.entries().iter(), self.all_apps_sort, &self.history);
+        let apps : Vec<AppEntry> = self.app_filter.apply(sorted.into_iter()).into_iter().cloned().collect();
+        ui.horizontal(|ui|{
+            ui.heading("Library");
+            ui.add_space(16.0);
+            sort_menu_ui(ui, &mut self.all_apps_sort);
+        });
+        ui.add_space(16.0);
+        if self.library.is_empty(){
+            ui.label("No apps added yet");
+            return;
+        } else if apps.is_empty(){
+            ui.label("No apps match the search");
+            return;
+        }
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui|{
+            ui.horizontal_wrapped(|ui|{
+                ui.spacing_mut().item_spacing = Vec2 { x: 16.0, y: 16.0 };
+                for entry in apps.iter(){
+                    let texture = self.cover_texture(ctx, entry, TextureTier::Large);
+                    if app_tile_ui(ui, &texture, entry, self.running_apps.contains_key(&entry.id)).clicked(){
+                        self.selected_app = Some(entry.id);
+                        self.central_view = CentralView::App;
+                    }
+                }
+            });
+        });
+    }
+
+    fn statistics_ui(&mut self, ui : &mut egui::Ui){ //The Statistics screen in the central panel
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui|{
+            ui.heading("Statistics");
+            ui.add_space(16.0);
+            ui.label(RichText::new(format!("Total play time: {}", time_from_millis(stats::total_play_time(&self.library)))).size(32.0));
+
+            ui.add_space(32.0);
+            ui.horizontal(|ui|{
+                ui.label("Play time per");
+                ui.selectable_value(&mut self.stats_period, Period::Day, "Day");
+                ui.selectable_value(&mut self.stats_period, Period::Week, "Week");
+                ui.selectable_value(&mut self.stats_period, Period::Month, "Month");
+            });
+            let count = match self.stats_period { Period::Day => 14, Period::Week => 12, Period::Month => 12 };
+            bar_chart_ui(ui, &stats::play_time_by_period(&self.history, self.stats_period, count, now_millis()));
+            ui.label(RichText::new("Only sessions recorded in the history show up here").weak());
+
+            ui.add_space(32.0);
+            ui.label("Most Played");
+            let top_apps = stats::top_apps(&self.library, 10);
+            if top_apps.is_empty(){
+                ui.label("Nothing played yet");
+            }
+            horizontal_bars_ui(ui, &top_apps);
+
+            ui.add_space(32.0);
+            ui.label("Groups");
+            let group_totals = stats::group_totals(&self.library);
+            if group_totals.is_empty(){
+                ui.label("No groups yet");
+            }
+            horizontal_bars_ui(ui, &group_totals);
+        });
+    }
+
+    fn session_editor_ui(&mut self, ctx : &egui::Context){ //The "Edit Session" window, opened from the history list
+        let Some(record) = self.editing_session.and_then(|id| self.history.get(id)).cloned() else {
+            self.editing_session = None;
+            return;
+        };
+        Window::new("Edit Session").show(ctx, |ui|{
+            ui.label(format!("Started: {} (UTC)", format_timestamp(record.start)));
+            ui.label(format!("Profile: {}", self.library.get(record.app).map(|entry| profile_name(entry, record.profile_id)).unwrap_or_default()));
+            ui.horizontal(|ui|{
+                ui.label("Duration");
+                ui.add(egui::DragValue::new(&mut self.current_session_minutes).suffix(" minutes"));
+            });
+            if ui.button("Save").clicked() || ui.input(|i| i.key_pressed(Key::Enter)){
+                self.set_session_duration(record.id, self.current_session_minutes * 60000);
+                self.editing_session = None;
+            }
+            if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(Key::Escape)){
+                self.editing_session = None;
+            }
+        });
+    }
+
+    fn profiles_ui(&mut self, ui : &mut egui::Ui, entry : &AppEntry){ //The launch profile editor in the "Edit App" window
+        if self.editing_profile >= entry.profiles.len(){
+            self.editing_profile = entry.default_profile_index();
+        }
+        ui.horizontal(|ui|{
+            ui.label("Profile");
+            egui::ComboBox::from_id_salt("Profile Picker")
+            .selected_text(entry.profile(self.editing_profile).map(|profile| profile.name.clone()).unwrap_or_default())
+            .show_ui(ui, |ui|{
+                for (index, profile) in entry.profiles.iter().enumerate(){
+                    ui.selectable_value(&mut self.editing_profile, index, &profile.name);
+                }
+            });
+            if ui.button("Add Profile [+]").clicked() && let Some(index) = self.library.add_profile(entry.id, &entry.unused_profile_name()){
+                self.editing_profile = index;
+            }
+        });
+
+        let Some(old_profile) = entry.profile(self.editing_profile) else { return };
+        let mut profile = old_profile.clone();
+
+        ui.add(egui::TextEdit::singleline(&mut profile.name).hint_text("Profile Name").min_size(Vec2 { x: 512.0, y: 0.0 }));
+        ui.horizontal(|ui|{
+            ui.add(egui::TextEdit::singleline(&mut profile.path).hint_text(&entry.path).min_size(Vec2 { x: 400.0, y: 0.0 }));
+            if ui.button("Browse").clicked(){
+                let files = FileDialog::new()
+                .set_directory(default_dialog_dir())
+                .pick_file();
+                if let Some(picked_path) = files && let Some(exe_path) = picked_path.to_str(){
+                    profile.path = exe_path.to_string();
+                }
+            }
+        });
+        let profile_path = entry.profile_path(&profile).to_string();
+        launch_options_ui(ui, &profile_path, &mut profile.options);
+
+        let mut is_removed = false;
+        ui.horizontal(|ui|{
+            if self.editing_profile == entry.default_profile_index(){
+                ui.label("This is the default profile");
+            } else if ui.button("Make Default").clicked(){
+                self.library.set_default_profile(entry.id, self.editing_profile);
+            }
+            if entry.profiles.len() > 1 && ui.button("Remove Profile").clicked(){
+                self.library.remove_profile(entry.id, self.editing_profile);
+                self.editing_profile = 0;
+                is_removed = true;
+            }
+        });
+
+        if !is_removed && &profile != old_profile{
+            self.library.set_profile(entry.id, self.editing_profile, profile);
         }
     }
 }
@@ -100,12 +828,27 @@ impl eframe::App for CatapultApp {
                     if ui.button("Quit").clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                     }
+                    if ui.button("Settings").clicked() {
+                        self.is_editing_settings = true;
+                    }
+                    #[cfg(target_os = "linux")]
+                    if ui.button("Import from Desktop Entries").clicked() {
+                        self.desktop_import = Some(scan_desktop_entries().into_iter().map(|entry| (entry, false)).collect());
+                    }
+                    if ui.button("Import Steam Library").clicked() {
+                        self.open_steam_import();
+                    }
                     if ui.button("Fullscreen").clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(true));
                     }
                     if ui.button("Hover Window").clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(false));
                     }
+                });
+                ui.menu_button("View", |ui| {
+                    ui.selectable_value(&mut self.central_view, CentralView::App, "App Details");
+                    ui.selectable_value(&mut self.central_view, CentralView::Grid, "Library Grid");
+                    ui.selectable_value(&mut self.central_view, CentralView::Statistics, "Statistics");
                 });
                     ui.add_space(16.0);
             });
@@ -118,61 +861,23 @@ impl eframe::App for CatapultApp {
             if self.is_app_selected{
                 Window::new("Confirm App Name").show(ctx, |ui|{
                     
-                    let sized_image : SizedTexture;
-
-                    if self.app_texture_handles.get(&self.current_path).is_none(){
-                        let color_icon = get_color_icon(self.current_path.clone(), [128,128]);
-                        let handle = ctx.load_texture("app_icon", color_icon.clone(), TextureOptions::LINEAR);
-                        sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(64.0, 64.0));
-                        self.app_texture_handles.insert(self.current_path.clone(), handle.clone());
-                    } else {
-                        let handle = self.app_texture_handles.get(&self.current_path).unwrap();
-                        sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(64.0, 64.0));
-                    }
+                    let handle = self.exe_icon_texture(ctx, &self.current_path.clone(), TextureTier::Medium);
+                    let sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(64.0, 64.0));
                     
                     
                     
                     ui.add(egui::Image::from_texture(sized_image));
 
-                    if self.apps_aliases.get(&self.current_path).is_some(){
-                        self.current_app_name = self.apps_aliases.get(&self.current_path).unwrap().to_string();
-                    }
-                    if self.current_app_name == get_executable_name(&self.current_path){
-                        self.current_app_name = "".to_string()
-                    }
-
                     ui.add(egui::TextEdit::singleline(&mut self.current_app_name).hint_text(get_executable_name(&self.current_path)).min_size(Vec2 { x: 512.0, y: 0.0 }));
 
-                    if self.current_app_name == "".to_string(){
-                        self.apps_aliases.insert(self.current_path.clone(), get_executable_name(&self.current_path));
-                    } else {
-                        self.apps_aliases.insert(self.current_path.clone(), self.current_app_name.clone());
-                    }
-
                     ui.label(RichText::new(format!("Executable Path: {}",&self.current_path)));
                     if ui.button("Add App").clicked() || ui.input(|i| i.key_pressed(Key::Enter)){
+                        self.library.add(&self.current_path, &self.current_app_name);
                         self.current_app_name = "".to_string();
-                        if ! self.apps.contains(&self.current_path){
-                            let _ = &mut self.apps.push(self.current_path.clone());
-                            self.app_play_time.insert(self.current_path.clone(), 0);
-                            self.apps.sort_by(|a, b| {
-                                let a_name = self.apps_aliases.get(a).unwrap().to_lowercase();
-                                let b_name = self.apps_aliases.get(b).unwrap().to_lowercase();
-                                a_name.cmp(&b_name)
-                            });
-                        } else {
-                            if self.app_play_time.get(&self.current_path.clone()).is_none(){
-                                self.app_play_time.insert(self.current_path.clone(), 0);
-                            }
-                            self.apps.sort_by(|a, b| {
-                                let a_name = self.apps_aliases.get(a).unwrap().to_lowercase();
-                                let b_name = self.apps_aliases.get(b).unwrap().to_lowercase();
-                                a_name.cmp(&b_name)
-                            });
-                        }
                         self.is_app_selected = false
                     };
                     if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(Key::Escape)){
+                        self.current_app_name = "".to_string();
                         self.is_app_selected = false
                     };
                 }); 
@@ -181,10 +886,9 @@ impl eframe::App for CatapultApp {
                 Window::new("Create Folder").show(ctx, |ui|{
                     ui.add(egui::TextEdit::singleline(&mut self.current_folder_name).hint_text("New Group").min_size(Vec2 { x: 512.0, y: 0.0 }));
                     if (ui.button("Add Group").clicked() || ui.input(|i| i.key_pressed(Key::Enter))) && self.current_folder_name != "".to_string(){
-                        let new_folder_content : Vec<String> = vec![self.selected_app.clone()];
-                        self.app_folders.insert(self.current_folder_name.clone(), new_folder_content);
-                        if !self.app_folder_names.contains(&self.current_folder_name){
-                            self.app_folder_names.push(self.current_folder_name.clone());
+                        self.library.create_group(&self.current_folder_name);
+                        if let Some(id) = self.selected_app {
+                            self.library.add_to_group(id, &self.current_folder_name);
                         }
                         self.current_folder_name = "".to_string();
                         self.is_folder_created = false;
@@ -201,14 +905,14 @@ impl eframe::App for CatapultApp {
 
             ui.add_space(32.0);
 
-            ui.label(format!("Count: {}", self.apps.len()));
+            ui.label(format!("Count: {}", self.library.len()));
 
             ui.add_space(32.0);
             
             if ui.button("Add App [+]").clicked() {
 
                 let files = FileDialog::new()
-                .set_directory("C:/")
+                .set_directory(default_dialog_dir())
                 .pick_file();
                 
                 if files.is_some(){
@@ -216,91 +920,88 @@ impl eframe::App for CatapultApp {
                     let path = picked_path.as_path();
                     let exe_path = path.to_str().unwrap();
                     self.current_path = exe_path.to_string();
+                    self.current_app_name = match self.library.find_by_path(&self.current_path) { //Re-adding an app lets the user rename it, so start from its current name
+                        Some(entry) if entry.name != get_executable_name(&entry.path) => entry.name.clone(),
+                        _ => "".to_string(),
+                    };
                     self.is_app_selected = true;
                 }
             };
             ui.add_space(16.0);
+            self.search_ui(ui);
+            ui.add_space(16.0);
             
             egui::ScrollArea::vertical().show(ui, |ui| {
                 ui.menu_button("ALL Apps", |ui| {
+                    sort_menu_ui(ui, &mut self.all_apps_sort);
+                    let sorted = sort_entries(self.library.entries().iter(), self.all_apps_sort, &self.history);
+                    let apps : Vec<AppEntry> = self.app_filter.apply(sorted.into_iter()).into_iter().cloned().collect();
                     egui::ScrollArea::vertical()
                     .max_width(480.0)
                     .max_height(240.0)
                     .auto_shrink([false, true])
                     .show(ui, |ui| {
-                        for app in &mut self.apps.iter(){
-
-                            let sized_image : SizedTexture;
+                        if self.library.is_empty(){
+                            ui.label("No apps added yet");
+                        } else if apps.is_empty(){
+                            ui.label("No apps match the search");
+                        }
+                        for entry in apps.iter(){
 
-                            if self.app_texture_handles.get(&app.to_string()).is_none(){
-                                let color_icon = get_color_icon(app.clone(), [128,128]);
-                                let handle = ctx.load_texture("app_icon", color_icon.clone(), TextureOptions::LINEAR);
-                                sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(48.0, 48.0));
-                                self.app_texture_handles.insert(app.clone(), handle.clone());
-                            } else {
-                                let handle = self.app_texture_handles.get(&app.to_string()).unwrap();
-                                sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(48.0, 48.0));
-                            }
+                            let handle = self.icon_texture(ctx, entry, TextureTier::Small);
+                            let sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(48.0, 48.0));
                             let icon = egui::Image::from_texture(sized_image);
 
-                            let app_name = self.apps_aliases.get(app);
-                            let text : RichText;
-                            if app_name.is_some(){
-                                text = RichText::new(app_name.unwrap().to_string()).size(24.0);
-                            } else {
-                                text = RichText::new(app).size(24.0);
-                            }
+                            let text = RichText::new(&entry.name).size(24.0);
 
                             if ui.add(egui::Button::image_and_text(icon.clone(), text.clone()).min_size(Vec2 { x: 32.0, y: 32.0 })).clicked(){
-                                self.selected_app = app.to_string();
+                                self.selected_app = Some(entry.id);
+                                self.central_view = CentralView::App;
                             }
                             ui.add_space(8.0);
                         }
                     });   
-                    ui.label(format!("Games:{}", self.apps.len()));
+                    if self.app_filter.is_active(){
+                        ui.label(format!("Games:{}/{}", apps.len(), self.library.len()));
+                    } else {
+                        ui.label(format!("Games:{}", self.library.len()));
+                    }
                 });
                 ui.add_space(24.0);
-                for folder in self.app_folder_names.iter(){
+                for folder in self.library.groups().to_vec(){
 
-                    ui.menu_button(folder, |ui| {
-                        //self.auto_shrink = false;
+                    ui.menu_button(&folder, |ui| {
+                        let group_size = self.library.group_entries(&folder).count();
+                        let mut sort_mode = self.group_sorts.get(&folder).copied().unwrap_or_default();
+                        if sort_menu_ui(ui, &mut sort_mode){
+                            self.group_sorts.insert(folder.clone(), sort_mode);
+                        }
+                        let sorted = sort_entries(self.library.group_entries(&folder), sort_mode, &self.history);
+                        let folder_apps : Vec<AppEntry> = self.app_filter.apply(sorted.into_iter()).into_iter().cloned().collect();
                         egui::ScrollArea::vertical()
                         .max_width(480.0)
                         .max_height(240.0)
                         .auto_shrink([false, true])
                         .show(ui,|ui| {
-                            if self.app_folders.get(folder).unwrap().len() == 0{
+                            if group_size == 0{
                                 ui.label("No apps in this group");
+                            } else if folder_apps.is_empty(){
+                                ui.label("No apps match the search");
                             } else {
-                                for app in self.app_folders.clone().get(folder).unwrap(){
-                                    let sized_image : SizedTexture;
-
-                                    if self.app_texture_handles.get(&app.to_string()).is_none(){
-                                        let color_icon = get_color_icon(app.clone(), [128,128]);
-                                        let handle = ctx.load_texture("app_icon", color_icon.clone(), TextureOptions::LINEAR);
-                                        sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(48.0, 48.0));
-                                        self.app_texture_handles.insert(app.clone(), handle.clone());
-                                    } else {
-                                        let handle = self.app_texture_handles.get(&app.to_string()).unwrap();
-                                        sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(48.0, 48.0));
-                                    }
+                                for entry in folder_apps.iter(){
+                                    let handle = self.icon_texture(ctx, entry, TextureTier::Small);
+                                    let sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(48.0, 48.0));
                                     let icon = egui::Image::from_texture(sized_image);
 
-                                    let app_name = self.apps_aliases.get(app);
-                                    let text : RichText;
-                                    if app_name.is_some(){
-                                        text = RichText::new(app_name.unwrap().to_string()).size(24.0);
-                                    } else {
-                                        text = RichText::new(app).size(24.0);
-                                    }
+                                    let text = RichText::new(&entry.name).size(24.0);
 
                                     ui.horizontal(|ui|{
                                         if ui.add(egui::Button::image_and_text(icon.clone(), text.clone()).min_size(Vec2 { x: 32.0, y: 32.0 })).clicked(){
-                                            self.selected_app = app.to_string();
+                                            self.selected_app = Some(entry.id);
+                                            self.central_view = CentralView::App;
                                         }
                                         if ui.add(egui::Button::new("Remove").min_size(Vec2 { x: 32.0, y: 32.0 })).clicked(){
-                                            let folder_vec = self.app_folders.get_mut(folder).unwrap();
-                                            folder_vec.retain(|a| a != app);
+                                            self.library.remove_from_group(entry.id, &folder);
                                         }
                                     });
 
@@ -310,7 +1011,15 @@ impl eframe::App for CatapultApp {
                             }
                         });
                         
-                        ui.label(format!("Games:{}", self.app_folders.get(folder).unwrap().len()));
+                        if self.app_filter.is_active(){
+                            ui.label(format!("Games:{}/{}", folder_apps.len(), group_size));
+                        } else {
+                            ui.label(format!("Games:{}", group_size));
+                        }
+                        if ui.button("Delete Group").clicked(){
+                            self.library.remove_group(&folder);
+                            self.group_sorts.remove(&folder);
+                        }
                     });
                 }
             });
@@ -318,84 +1027,154 @@ impl eframe::App for CatapultApp {
 
         egui::CentralPanel::default()
             .show(ctx, |ui|{
-                if self.selected_app != "".to_string() && self.apps.contains(&self.selected_app){
-                    let color_icon = get_color_icon(self.selected_app.clone(), [128,128]);
-                    let handle = ctx.load_texture("app_icon", color_icon.clone(), TextureOptions::LINEAR);
-                    let sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(512.0, 512.0));
-                    ui.add(egui::Image::from_texture(sized_image));
-                    let app_name = RichText::new(self.apps_aliases.get(&self.selected_app).unwrap()).size(64.0);
-                    ui.add(egui::Label::new(app_name));
-                    let button_text = RichText::new("LAUNCH >").size(64.0);
-                    if ui.add(egui::Button::new(button_text)).clicked(){
-                        let pid = open_app(&self.selected_app);
-                        self.sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
-                        track_app(pid, &self);
-                        self.running_apps.insert(self.selected_app.clone(), pid);
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
-                    };
+                if self.central_view == CentralView::Statistics{
+                    self.statistics_ui(ui);
+                } else if self.central_view == CentralView::Grid{
+                    self.grid_ui(ctx, ui);
+                } else if let Some(entry) = self.selected_app.and_then(|id| self.library.get(id)).cloned(){
+                    if let Some(background) = self.artwork_texture(ctx, &entry, ArtworkKind::Background, TextureTier::Full) && !self.is_placeholder(&background){ //Nothing behind the page until it has loaded
+                        background_ui(ui, &background);
+                    }
+                    if let Some(cover) = self.artwork_texture(ctx, &entry, ArtworkKind::Cover, TextureTier::Large){
+                        ui.add(egui::Image::from_texture(SizedTexture::from_handle(&cover)).max_size(egui::vec2(512.0, 512.0)));
+                    } else {
+                        let handle = self.icon_texture(ctx, &entry, TextureTier::Large);
+                        let sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(512.0, 512.0));
+                        ui.add(egui::Image::from_texture(sized_image));
+                    }
+                    if let Some(logo) = self.artwork_texture(ctx, &entry, ArtworkKind::Logo, TextureTier::Large) && !self.is_placeholder(&logo){ //The name stands in for it until it has loaded
+                        ui.add(egui::Image::from_texture(SizedTexture::from_handle(&logo)).max_size(egui::vec2(512.0, 128.0))).on_hover_text(&entry.name);
+                    } else {
+                        let app_name = RichText::new(&entry.name).size(64.0);
+                        ui.add(egui::Label::new(app_name));
+                    }
+                    ui.horizontal(|ui|{ //A split button, the main half launches the default profile and the arrow picks another one
+                        if self.running_apps.contains_key(&entry.id){ //Already running, launching it again would start a second session for it
+                            ui.disable();
+                        }
+                        let button_text = RichText::new("LAUNCH >").size(64.0);
+                        if ui.add(egui::Button::new(button_text)).on_disabled_hover_text("Already running").clicked(){
+                            self.launch_app(ctx, &entry, entry.default_profile_index());
+                        };
+                        ui.menu_button(RichText::new("v").size(64.0), |ui|{
+                            for (index, profile) in entry.profiles.iter().enumerate(){
+                                let profile_text = if index == entry.default_profile_index() { format!("{} (Default)", profile.name) } else { profile.name.clone() };
+                                if ui.button(profile_text).clicked(){
+                                    self.launch_app(ctx, &entry, index);
+                                }
+                            }
+                        });
+                    });
                     ui.add_space(8.0);
                     if ui.add(egui::Button::new("Edit App")).clicked(){
+                        self.current_app_name = if entry.name == get_executable_name(&entry.path) { "".to_string() } else { entry.name.clone() };
+                        self.current_tags = entry.tags.join(", ");
+                        self.editing_profile = entry.default_profile_index();
                         self.is_editing_app = true;
                     }
                     ui.menu_button("Add to Group", |ui|{
-                        let folder_names: Vec<String> = self.app_folder_names.clone();
+                        let folder_names: Vec<String> = self.library.groups().to_vec();
                         if ui.button("New Group [+]").clicked() {
                             self.is_folder_created = true;
                         }
                         for folder in folder_names{
                             if ui.button(&folder).clicked(){
-                                let folder_vec = self.app_folders.get_mut(&folder).unwrap();
-                                if !folder_vec.contains(&self.selected_app.clone()){
-                                    folder_vec.push(self.selected_app.clone());
-                                }
+                                self.library.add_to_group(entry.id, &folder);
                                 ctx.request_repaint();
                             }
                         }
                     });
-                    let readable_time = time_from_millis(*self.app_play_time.get(&self.selected_app).unwrap_or(&(0 as u64)));
+                    let readable_time = time_from_millis(entry.play_time);
                     ui.label(format!("Time played: {}", readable_time));
+                    if entry.profiles.len() > 1{
+                        for profile in entry.profiles.iter(){
+                            ui.label(format!("    {}: {}", profile.name, time_from_millis(profile.play_time)));
+                        }
+                    }
+                    ui.add_space(8.0);
+                    if ui.button(if self.is_showing_history { "Hide History" } else { "History" }).clicked(){
+                        self.is_showing_history = !self.is_showing_history;
+                    }
+                    if self.is_showing_history{
+                        self.history_ui(ui, &entry);
+                    }
                 } else {
                     ui.label("Select an App");
                 };
-                if self.is_editing_app{
+                if self.is_editing_app && let Some(entry) = self.selected_app.and_then(|id| self.library.get(id)).cloned(){
                     Window::new("Edit App").show(ctx, |ui|{
 
-                        let sized_image : SizedTexture;
-
-                        if self.app_texture_handles.get(&self.selected_app).is_none(){
-                            let color_icon = get_color_icon(self.selected_app.clone(), [128,128]);
-                            let handle = ctx.load_texture("app_icon", color_icon.clone(), TextureOptions::LINEAR);
-                            sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(64.0, 64.0));
-                            self.app_texture_handles.insert(self.selected_app.clone(), handle.clone());
-                        } else {
-                            let handle = self.app_texture_handles.get(&self.selected_app).unwrap();
-                            sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(64.0, 64.0));
-                        }
+                        let handle = self.icon_texture(ctx, &entry, TextureTier::Medium);
+                        let sized_image = egui::load::SizedTexture::new(handle.id(), egui::vec2(64.0, 64.0));
                         
                         ui.add(egui::Image::from_texture(sized_image));
 
-                        if self.apps_aliases.get(&self.selected_app).is_some(){
-                            self.current_app_name = self.apps_aliases.get(&self.selected_app).unwrap().to_string();
+                        if ui.add(egui::TextEdit::singleline(&mut self.current_app_name).hint_text(get_executable_name(&entry.path)).min_size(Vec2 { x: 512.0, y: 0.0 })).changed(){
+                            self.library.rename(entry.id, &self.current_app_name);
                         }
-                        if self.current_app_name == get_executable_name(&self.selected_app){
-                            self.current_app_name = "".to_string()
+
+                        ui.label(RichText::new(format!("Executable Path: {}",&entry.path)));
+                        if ui.button("Change Executable").clicked(){ //For when the app has been moved, the name, play time and groups all stay with it
+                            let files = FileDialog::new()
+                            .set_directory(default_dialog_dir())
+                            .pick_file();
+                            if let Some(picked_path) = files && let Some(exe_path) = picked_path.to_str(){
+                                self.library.set_path(entry.id, exe_path);
+                            }
                         }
 
-                        ui.add(egui::TextEdit::singleline(&mut self.current_app_name).hint_text(get_executable_name(&self.selected_app)).min_size(Vec2 { x: 512.0, y: 0.0 }));
+                        ui.add_space(8.0);
+                        ui.label("Tracked Process Name");
+                        let mut process_name = entry.tracked_process_name.clone();
+                        if ui.add(egui::TextEdit::singleline(&mut process_name).hint_text("game.exe (for apps started through a launcher)").min_size(Vec2 { x: 512.0, y: 0.0 })).changed(){
+                            self.library.set_tracked_process_name(entry.id, &process_name);
+                        }
+                        ui.label("Tracked Folder");
+                        let mut tracked_dir = entry.tracked_dir.clone();
+                        if ui.add(egui::TextEdit::singleline(&mut tracked_dir).hint_text("Any executable in this folder counts as the app running").min_size(Vec2 { x: 512.0, y: 0.0 })).changed(){
+                            self.library.set_tracked_dir(entry.id, &tracked_dir);
+                        }
 
-                        if self.current_app_name == "".to_string(){
-                            self.apps_aliases.insert(self.selected_app.clone(), get_executable_name(&self.selected_app));
-                        } else {
-                            self.apps_aliases.insert(self.selected_app.clone(), self.current_app_name.clone());
+                        ui.add_space(8.0);
+                        ui.label("Artwork");
+                        egui::Grid::new("Artwork").spacing([16.0, 8.0]).show(ui, |ui|{
+                            for kind in ArtworkKind::ALL{
+                                ui.label(kind.label());
+                                match self.artwork_texture(ctx, &entry, kind, TextureTier::Medium){
+                                    Some(handle) => ui.add(egui::Image::from_texture(SizedTexture::from_handle(&handle)).max_size(egui::vec2(64.0, 64.0))),
+                                    None => ui.label(RichText::new("Not set").weak()),
+                                };
+                                if ui.button("Choose Image").clicked(){
+                                    self.choose_artwork(&entry, kind);
+                                }
+                                if !entry.artwork.get(kind).is_empty() && ui.button("Clear").clicked(){
+                                    self.replace_artwork(entry.id, kind, "");
+                                }
+                                ui.end_row();
+                            }
+                        });
+
+                        ui.add_space(8.0);
+                        ui.label("Tags");
+                        if ui.add(egui::TextEdit::singleline(&mut self.current_tags).hint_text("Comma separated, e.g. roguelike, co-op").min_size(Vec2 { x: 512.0, y: 0.0 })).changed(){
+                            let tags : Vec<String> = self.current_tags.split(',').map(|tag| tag.to_string()).collect();
+                            self.library.set_tags(entry.id, &tags);
                         }
 
-                        ui.label(RichText::new(format!("Executable Path: {}",&self.selected_app)));
+                        ui.add_space(8.0);
+                        self.profiles_ui(ui, &entry);
+                        ui.add_space(8.0);
                         
                         if ui.button("Remove").clicked(){
-                            self.apps.retain(|path| path != &self.selected_app);
-                            if self.apps.len() > 0 {
-                                self.selected_app = self.apps.get(0).unwrap().to_string();
+                            if let Some(removed) = self.library.remove(entry.id){
+                                for kind in ArtworkKind::ALL{
+                                    remove_artwork(removed.artwork.get(kind));
+                                }
                             }
+                            self.history.remove_app(entry.id);
+                            self.untrack_app(entry.id);
+                            self.selected_app = self.library.entries().first().map(|first| first.id);
+                            self.is_editing_app = false;
                         }
 
                         if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(Key::Escape)){
@@ -404,40 +1183,93 @@ impl eframe::App for CatapultApp {
                 }); 
             }
 
-            ctx.request_repaint();        
         });
 
-        self.delta_time = Instant::now().checked_duration_since(self.last_instant).unwrap();
-        self.last_instant = Instant::now();
+        self.session_editor_ui(ctx);
+        self.settings_ui(ctx);
+        #[cfg(target_os = "linux")]
+        self.desktop_import_ui(ctx);
+        self.steam_import_ui(ctx);
+        self.notifications.show(ctx);
 
-        for app in self.running_apps.keys(){
-            let pid = self.running_apps.get(app).unwrap();
-            self.sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
-            let process = track_app(*pid, &self);
-            if process.is_none(){
-                self.app_to_remove = app.clone();
-            } else {
-                let current_play_time = *self.app_play_time.get(app).unwrap_or(&0);
-                self.app_play_time.insert(app.clone(), current_play_time + self.delta_time.as_millis() as u64);
+        self.send_library_to_tracker();
+
+        let loaded_images = self.icon_loader.as_mut().map(|icon_loader| icon_loader.loaded()).unwrap_or_default();
+        for (key, tier, image) in loaded_images{
+            match image{
+                Some(image) => {
+                    let handle = ctx.load_texture(&key, image, TextureOptions::LINEAR);
+                    self.app_texture_handles.insert(key, tier, handle);
+                }
+                None => self.forget_broken_image(&key),
             }
         }
+        self.app_texture_handles.end_frame();
 
-        if self.app_to_remove != "".to_string(){
-            self.running_apps.remove(&self.app_to_remove);
-            self.app_to_remove = "".to_string();
+        let events : Vec<TrackerEvent> = self.tracker.as_ref().map(|tracker| tracker.events().collect()).unwrap_or_default();
+        for event in events{
+            match event{
+                TrackerEvent::Started { app, record : Some(record), .. } if self.history.get(record).is_some() => { //Not if the session was deleted before the tracker picked it up
+                    self.running_apps.insert(app, record);
+                }
+                TrackerEvent::Started { app, profile, record : None, start } => self.attach_session(app, profile, start),
+                TrackerEvent::Played { app, profile, record, millis, idle, now } if self.running_apps.get(&app) == Some(&record) => { //Sessions that were untracked may still have events on the way
+                    self.library.add_play_time(app, profile, millis);
+                    self.history.add_time(record, millis, idle, now);
+                }
+                TrackerEvent::Stopped { app, record, exit_code, now } if self.running_apps.get(&app) == Some(&record) => {
+                    self.history.finish(record, now, exit_code);
+                    self.running_apps.remove(&app);
+                }
+                TrackerEvent::NeverStarted { app, record } if self.running_apps.get(&app) == Some(&record) => { //Nothing was played, so there's no play time to take back either
+                    self.history.remove(record);
+                    self.running_apps.remove(&app);
+                    if let Some(entry) = self.library.get(app){
+                        self.notifications.error(format!("{} didn't start, no session was recorded", entry.name));
+                    }
+                }
+                TrackerEvent::Started { .. } | TrackerEvent::Played { .. } | TrackerEvent::Stopped { .. } | TrackerEvent::NeverStarted { .. } => {}
+            }
         }
 
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Some(tracker) = self.tracker.as_mut(){
+            tracker.shutdown(); //Writes a last checkpoint, so the time since the last one is counted on the next start
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn is_desktop_entry_added(library : &Library, entry : &DesktopEntry) -> bool{ //Entries can share a program, so it takes the same program with the same arguments (or the same Steam game) to count as added
+    library.entries().iter().any(|added| {
+        let is_same_game = entry.steam_app_id.is_some_and(|app_id| added.steam_app_id == app_id);
+        let is_same_command = added.path == entry.program && added.profiles.first().is_some_and(|profile| profile.options.args == entry.options.args); //Also Steam shortcuts that were imported before their game was installed
+        is_same_game || is_same_command
+    })
 }
 
+fn is_steam_game_added(library : &Library, game : &SteamGame) -> bool{
+    library.entries().iter().any(|added| added.steam_app_id == game.app_id)
+}
 
-fn get_executable_name(path : &String) -> String{ //Gets the name of an executable from its path, to use as the default app name if the user doesn't specify one. For example, "C:\Program Files\Example\example.exe", will return "example"
-    let mut new_path = path.clone();
-    let split_path : Vec<&str> = new_path.split("\\").collect();
-    new_path = (*split_path.get(split_path.iter().count() - 1).unwrap()).to_string().replace(".exe", "");
-    new_path
+fn profile_name(entry : &AppEntry, profile_id : ProfileId) -> String{ //For the history, whose sessions can outlive the profile they were played with
+    entry.profile_by_id(profile_id).map(|profile| profile.name.clone()).unwrap_or("(Removed)".to_string())
 }
 
+fn default_dialog_dir() -> std::path::PathBuf{ //Where the file pickers start, the root of the system drive on Windows and the home directory elsewhere
+    if cfg!(windows){
+        return std::path::PathBuf::from("C:/");
+    }
+    std::env::var_os("HOME").map(std::path::PathBuf::from).unwrap_or_else(|| std::path::PathBuf::from("/"))
+}
+
+fn checkpoint_path() -> Option<std::path::PathBuf>{ //Where the tracker thread keeps the sessions that are currently running
+    eframe::storage_dir(APP_NAME).map(|dir| dir.join("running_sessions.txt"))
+}
+
+
 fn set_stylings(ctx: &egui::Context){ //Makes egui look pretty
     let mut style = (*ctx.style()).clone();
     style.text_styles = [
@@ -469,30 +1301,151 @@ fn set_stylings(ctx: &egui::Context){ //Makes egui look pretty
     ctx.set_theme(ThemePreference::Dark);
 }
 
-fn open_app(name : &String) -> usize{ //Given an executable path, open the executable and return its PID so we can track it with track_app. If the app fails to open, return 0 (which will never be a valid PID)
-    let mut command = Command::new(name);
-    
-    if let Ok(child) = command.spawn() {
-        child.id() as usize
-    } else {
-        println!("open command didn't start");
-        0
+fn background_ui(ui : &mut egui::Ui, background : &TextureHandle){ //Fills the panel with a dimmed image, cropped rather than stretched to fit
+    let rect = ui.max_rect();
+    let image_size = background.size_vec2();
+    let scale = (rect.width() / image_size.x).max(rect.height() / image_size.y);
+    let uv = egui::Rect::from_center_size(egui::pos2(0.5, 0.5), rect.size() / (image_size * scale));
+    ui.painter().image(background.id(), rect, uv, Color32::from_white_alpha(48));
+}
+
+fn app_tile_ui(ui : &mut egui::Ui, cover : &TextureHandle, entry : &AppEntry, is_running : bool) -> egui::Response { //One tile of the grid: the cover, the name under it and a play time badge in the corner
+    let tile_size = Vec2 { x: 200.0, y: 248.0 };
+    let padding = 8.0;
+    let (rect, response) = ui.allocate_exact_size(tile_size, egui::Sense::click());
+    if ui.is_rect_visible(rect){
+        let visuals = ui.style().interact(&response);
+        ui.painter().rect_filled(rect, 8.0, visuals.weak_bg_fill);
+        ui.painter().rect_stroke(rect, 8.0, visuals.bg_stroke, egui::StrokeKind::Inside);
+
+        let cover_rect = egui::Rect::from_min_size(rect.min + Vec2::splat(padding), Vec2::splat(tile_size.x - padding * 2.0));
+        egui::Image::from_texture(SizedTexture::from_handle(cover)).paint_at(ui, cover_rect);
+
+        let mut name = egui::text::LayoutJob::simple_singleline(entry.name.clone(), FontId::proportional(18.0), visuals.text_color());
+        name.wrap = egui::text::TextWrapping::truncate_at_width(cover_rect.width());
+        let name = ui.painter().layout_job(name);
+        let name_pos = egui::pos2(rect.center().x - name.size().x / 2.0, cover_rect.bottom() + (rect.bottom() - cover_rect.bottom() - name.size().y) / 2.0);
+        ui.painter().galley(name_pos, name, visuals.text_color());
+
+        let badge_text = if is_running { "Running".to_string() } else { short_time_from_millis(entry.play_time) };
+        let badge = ui.painter().layout_no_wrap(badge_text, FontId::proportional(14.0), Color32::WHITE);
+        let badge_rect = egui::Rect::from_min_size(egui::pos2(cover_rect.right() - badge.size().x - 12.0, cover_rect.top() + 4.0), badge.size() + Vec2 { x: 8.0, y: 4.0 });
+        ui.painter().rect_filled(badge_rect, 4.0, if is_running { Color32::from_rgb(40, 140, 60) } else { Color32::from_black_alpha(180) });
+        ui.painter().galley(badge_rect.min + Vec2 { x: 4.0, y: 2.0 }, badge, Color32::WHITE);
     }
+    response.on_hover_text(&entry.name)
 }
 
-fn track_app(pid : usize, app : &CatapultApp) -> Option<&Process>{ //Given a PID, return the corresponding Process struct from sysinfo, which we can use to track if the app is still running and get other info if we want
-    let process = app.sys.process(Pid::from(pid));
-    process
+fn sort_menu_ui(ui : &mut egui::Ui, sort_mode : &mut SortMode) -> bool { //A "Sort: ..." submenu at the top of an app list, returns whether the mode was changed
+    let mut is_changed = false;
+    ui.menu_button(format!("Sort: {}", sort_mode.label()), |ui|{
+        for mode in SortMode::ALL{
+            if ui.selectable_value(sort_mode, mode, mode.label()).clicked(){
+                is_changed = true;
+            }
+        }
+    });
+    is_changed
 }
 
-fn get_color_icon(exe_path : String, size : [usize; 2]) -> ColorImage{ //Loads the file icon for the given executable path and converts it to an egui ColorImage, which can then be loaded as a texture and displayed in the UI
-    let app_icon = get_file_icon(exe_path.clone(), 128).expect("Failed to get icon");
-    let app_icon_image = RgbaImage::from_raw(app_icon.width, app_icon.height, app_icon.pixels)
-        .map(DynamicImage::ImageRgba8)
-        .expect("Failed to convert image");
-                    
-    let color_icon = egui::ColorImage::from_rgba_premultiplied(size, app_icon_image.as_bytes());
-    color_icon
+fn launch_options_ui(ui : &mut egui::Ui, path : &str, launch : &mut LaunchOptions){ //The arguments, working directory and environment variable editors in the "Edit App" window
+    ui.label("Arguments");
+    let mut arg_to_remove : Option<usize> = None;
+    for (index, arg) in launch.args.iter_mut().enumerate(){
+        ui.horizontal(|ui|{
+            ui.add(egui::TextEdit::singleline(arg).hint_text("-windowed").min_size(Vec2 { x: 400.0, y: 0.0 }));
+            if ui.button("X").clicked(){
+                arg_to_remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = arg_to_remove{
+        launch.args.remove(index);
+    }
+    if ui.button("Add Argument [+]").clicked(){
+        launch.args.push("".to_string());
+    }
+
+    ui.add_space(8.0);
+    ui.label("Working Directory");
+    let default_dir = working_dir(path, &LaunchOptions::default()).map(|dir| dir.display().to_string()).unwrap_or_default();
+    ui.horizontal(|ui|{
+        ui.add(egui::TextEdit::singleline(&mut launch.working_dir).hint_text(&default_dir).min_size(Vec2 { x: 400.0, y: 0.0 }));
+        if ui.button("Browse").clicked(){
+            let folder = FileDialog::new()
+            .set_directory(&default_dir)
+            .pick_folder();
+            if let Some(folder) = folder && let Some(folder) = folder.to_str(){
+                launch.working_dir = folder.to_string();
+            }
+        }
+    });
+
+    ui.add_space(8.0);
+    ui.label("Environment Variables");
+    let mut env_to_remove : Option<usize> = None;
+    for (index, (key, value)) in launch.env.iter_mut().enumerate(){
+        ui.horizontal(|ui|{
+            ui.add(egui::TextEdit::singleline(key).hint_text("DXVK_HUD").min_size(Vec2 { x: 180.0, y: 0.0 }));
+            ui.label("=");
+            ui.add(egui::TextEdit::singleline(value).hint_text("1").min_size(Vec2 { x: 180.0, y: 0.0 }));
+            if ui.button("X").clicked(){
+                env_to_remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = env_to_remove{
+        launch.env.remove(index);
+    }
+    if ui.button("Add Variable [+]").clicked(){
+        launch.env.push(("".to_string(), "".to_string()));
+    }
+}
+
+fn short_time_from_millis(millis : u64) -> String{ //A shorter time_from_millis for chart labels
+    let minutes = millis / 60000;
+    if minutes >= 60 { format!("{}h {}m", minutes / 60, minutes % 60) } else { format!("{}m", minutes) }
+}
+
+fn bar_chart_ui(ui : &mut egui::Ui, bars : &[(String, u64)]){ //A vertical bar chart with a label under each bar
+    let chart_height = 200.0;
+    let label_height = 24.0;
+    let width = ui.available_width().min(960.0);
+    let (response, painter) = ui.allocate_painter(Vec2 { x: width, y: chart_height + label_height * 2.0 }, egui::Sense::hover());
+    let rect = response.rect;
+    let max = bars.iter().map(|(_, value)| *value).max().unwrap_or(0).max(1);
+    let slot_width = width / bars.len().max(1) as f32;
+    let bar_color = Color32::from_hex("#323749").unwrap();
+    let text_color = ui.visuals().text_color();
+    let font = FontId::new(12.0, FontFamily::Monospace);
+
+    for (index, (label, value)) in bars.iter().enumerate(){
+        let left = rect.left() + slot_width * index as f32;
+        let bar_height = chart_height * (*value as f32 / max as f32);
+        let bottom = rect.top() + label_height + chart_height;
+        let bar = egui::Rect::from_min_max(egui::pos2(left + slot_width * 0.15, bottom - bar_height), egui::pos2(left + slot_width * 0.85, bottom));
+        painter.rect_filled(bar, 2.0, bar_color);
+        if *value > 0{
+            painter.text(egui::pos2(bar.center().x, bar.top() - 2.0), egui::Align2::CENTER_BOTTOM, short_time_from_millis(*value), font.clone(), text_color);
+        }
+        painter.text(egui::pos2(bar.center().x, bottom + 4.0), egui::Align2::CENTER_TOP, label, font.clone(), text_color);
+    }
+}
+
+fn horizontal_bars_ui(ui : &mut egui::Ui, bars : &[(String, u64)]){ //A name, bar and time per row, for the top apps and group totals
+    let max = bars.iter().map(|(_, value)| *value).max().unwrap_or(0).max(1);
+    let bar_color = Color32::from_hex("#323749").unwrap();
+    egui::Grid::new(ui.next_auto_id()).spacing([16.0, 8.0]).show(ui, |ui|{
+        for (label, value) in bars.iter(){
+            ui.label(label);
+            let (response, painter) = ui.allocate_painter(Vec2 { x: 320.0, y: 18.0 }, egui::Sense::hover());
+            let mut bar = response.rect;
+            bar.set_width(bar.width() * (*value as f32 / max as f32));
+            painter.rect_filled(bar, 2.0, bar_color);
+            ui.label(short_time_from_millis(*value));
+            ui.end_row();
+        }
+    });
 }
 
 fn time_from_millis(millis : u64) -> String{
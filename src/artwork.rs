@@ -0,0 +1,167 @@
+use std::{collections::HashMap, fs, io, path::{Path, PathBuf}};
+use egui::ColorImage;
+use image::imageops::FilterType;
+
+use crate::app::APP_NAME;
+use crate::dates::now_millis;
+use crate::library::{AppEntry, AppId, ArtworkKind, get_executable_name};
+
+//Custom icons, covers, backgrounds and logos. Images the user picks for an app are copied into a directory Catapult manages next to its save, so they keep working if the originals are moved or deleted.
+//Apps without one can also get artwork from a folder of images named after games (see ArtworkFolder).
+
+pub fn artwork_dir() -> Option<PathBuf> {
+    eframe::storage_dir(APP_NAME).map(|dir| dir.join("artwork"))
+}
+
+pub fn artwork_path(file_name : &str) -> Option<PathBuf> {
+    if file_name.is_empty() {
+        return None;
+    }
+    artwork_dir().map(|dir| dir.join(file_name))
+}
+
+/// Copies the image at `source` into the artwork directory and returns the file name it was given. Fails if it isn't an image we can read.
+pub fn import_artwork(source : &Path, app : AppId, kind : ArtworkKind) -> io::Result<String> {
+    if is_svg(source) {
+        render_svg(source, 16).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Couldn't read the SVG"))?;
+    } else {
+        image::image_dimensions(source).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    }
+    let dir = artwork_dir().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Couldn't find a directory to keep artwork in"))?;
+    fs::create_dir_all(&dir)?;
+    let extension = source.extension().and_then(|extension| extension.to_str()).unwrap_or("png").to_lowercase();
+    let file_name = format!("{}_{}_{}.{}", app, kind.label().to_lowercase(), now_millis(), extension); //Every import gets a new name, so a texture cached for the image it replaces is never reused
+    fs::copy(source, dir.join(&file_name))?;
+    Ok(file_name)
+}
+
+pub fn remove_artwork(file_name : &str) {
+    if let Some(path) = artwork_path(file_name) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Reads an image, scaled down to at most `max_size` pixels along its longest side. None if it's gone or can't be decoded.
+pub fn load_image(path : &Path, max_size : u32) -> Option<ColorImage> {
+    if is_svg(path) {
+        return render_svg(path, max_size);
+    }
+    let mut image = image::open(path).ok()?;
+    if image.width() > max_size || image.height() > max_size { //A 4K wallpaper doesn't need to be a 4K texture
+        image = image.resize(max_size, max_size, FilterType::Triangle);
+    }
+    let image = image.to_rgba8();
+    Some(ColorImage::from_rgba_unmultiplied([image.width() as usize, image.height() as usize], image.as_raw()))
+}
+
+pub fn is_svg(path : &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("svg"))
+}
+
+/// Renders an SVG, like the scalable icons in icon themes, so its longest side is `size` pixels. None if it can't be parsed.
+pub fn render_svg(path : &Path, size : u32) -> Option<ColorImage> {
+    let tree = resvg::usvg::Tree::from_data(&fs::read(path).ok()?, &resvg::usvg::Options::default()).ok()?;
+    let tree_size = tree.size();
+    let scale = size as f32 / tree_size.width().max(tree_size.height());
+    let (width, height) = ((tree_size.width() * scale).round().max(1.0) as u32, (tree_size.height() * scale).round().max(1.0) as u32);
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)?;
+    resvg::render(&tree, resvg::tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    Some(ColorImage::from_rgba_premultiplied([width as usize, height as usize], pixmap.data()))
+}
+
+const IMAGE_EXTENSIONS : [&str; 3] = ["png", "jpg", "jpeg"];
+
+struct FolderImage {
+    name : String, //The file name without the kind suffix or extension, normalized (see normalize_name)
+    kind : ArtworkKind,
+    path : PathBuf,
+}
+
+/// A folder of images named after games, like "Hollow Knight.png" for a cover or "hollow_knight_hero.jpg" for a background. Apps are matched to images by name or executable name, ignoring case, spacing and punctuation, and allowing for small differences.
+#[derive(Default)]
+pub struct ArtworkFolder {
+    images : Vec<FolderImage>,
+    matches : HashMap<(AppId, ArtworkKind), (String, Option<PathBuf>)>, //The match found for each app, along with the name and path it was found for, so renaming or moving the app finds it again
+}
+
+impl ArtworkFolder {
+    /// Reads the image file names in `dir`. An empty or missing folder matches nothing.
+    pub fn scan(dir : &Path) -> Self {
+        let images = fs::read_dir(dir).into_iter().flatten().flatten()
+            .map(|file| file.path())
+            .filter(|path| path.extension().and_then(|extension| extension.to_str()).is_some_and(|extension| IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str())))
+            .filter_map(|path| {
+                let stem = path.file_stem()?.to_str()?.to_string();
+                let (name, kind) = split_kind(&stem);
+                Some(FolderImage { name : normalize_name(name), kind, path })
+            })
+            .filter(|image| !image.name.is_empty())
+            .collect();
+        Self { images, matches : HashMap::new() }
+    }
+
+    /// The image of the given kind that best matches the app, if any is close enough.
+    pub fn find(&mut self, entry : &AppEntry, kind : ArtworkKind) -> Option<PathBuf> {
+        let key = format!("{}\n{}", entry.name, entry.path);
+        if let Some((matched_key, path)) = self.matches.get(&(entry.id, kind)) && *matched_key == key {
+            return path.clone();
+        }
+        let names = [normalize_name(&entry.name), normalize_name(&get_executable_name(&entry.path))];
+        let path = self.images.iter()
+            .filter(|image| image.kind == kind)
+            .filter_map(|image| names.iter().filter_map(|name| match_distance(name, &image.name)).min().map(|distance| (distance, image)))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, image)| image.path.clone());
+        self.matches.insert((entry.id, kind), (key, path.clone()));
+        path
+    }
+
+    /// Stops matching an image, for when it turned out it can't be loaded.
+    pub fn forget(&mut self, path : &Path) {
+        self.images.retain(|image| image.path != path);
+        self.matches.clear();
+    }
+}
+
+fn split_kind(stem : &str) -> (&str, ArtworkKind) { //Works out what an image is for from a suffix like "_hero" or " logo", images without one are covers
+    let suffixes = [
+        ("hero", ArtworkKind::Background), ("background", ArtworkKind::Background), ("bg", ArtworkKind::Background),
+        ("logo", ArtworkKind::Logo),
+        ("icon", ArtworkKind::Icon),
+        ("cover", ArtworkKind::Cover), ("grid", ArtworkKind::Cover), ("poster", ArtworkKind::Cover),
+    ];
+    if let Some(index) = stem.rfind(['_', '-', ' ', '.']) {
+        let suffix = stem[index + 1..].to_lowercase();
+        if let Some((_, kind)) = suffixes.iter().find(|(name, _)| *name == suffix) {
+            return (&stem[..index], *kind);
+        }
+    }
+    (stem, ArtworkKind::Cover)
+}
+
+fn normalize_name(name : &str) -> String { //"Hollow Knight: Silksong" and "hollow_knight-silksong" both become "hollowknightsilksong"
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(|c| c.to_lowercase()).collect()
+}
+
+fn match_distance(name : &str, image_name : &str) -> Option<usize> { //How many characters would have to change to turn one name into the other, None if they're too different to be the same game
+    if name.is_empty() {
+        return None;
+    }
+    let distance = edit_distance(name, image_name);
+    (distance <= name.chars().count().max(image_name.chars().count()) / 5).then_some(distance)
+}
+
+fn edit_distance(a : &str, b : &str) -> usize { //Levenshtein distance, keeping only one row of the table at a time
+    let b : Vec<char> = b.chars().collect();
+    let mut row : Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut previous_diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous_diagonal + if a_char == *b_char { 0 } else { 1 };
+            previous_diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
@@ -0,0 +1,52 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+//Just enough calendar maths to show and group session timestamps, all in UTC
+
+pub const MILLIS_PER_DAY : u64 = 24 * 60 * 60 * 1000;
+
+/// Milliseconds since the unix epoch.
+pub fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_millis() as u64).unwrap_or(0)
+}
+
+/// Turns a count of days since the unix epoch into a (year, month, day) date. See https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub fn civil_from_days(days : i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Formats a unix timestamp in milliseconds as "YYYY-MM-DD HH:MM".
+pub fn format_timestamp(millis : u64) -> String {
+    let (year, month, day) = civil_from_days((millis / MILLIS_PER_DAY) as i64);
+    let minutes_into_day = (millis % MILLIS_PER_DAY) / 60000;
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, minutes_into_day / 60, minutes_into_day % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_days_to_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(19782), (2024, 2, 29)); //Leap day
+        assert_eq!(civil_from_days(19783), (2024, 3, 1));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29)); //Divisible by 400, so still a leap year
+        assert_eq!(civil_from_days(47541), (2100, 3, 1)); //Divisible by 100, so no leap day
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(19782 * MILLIS_PER_DAY + (23 * 60 + 59) * 60000 + 59999), "2024-02-29 23:59");
+    }
+}
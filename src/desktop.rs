@@ -0,0 +1,257 @@
+use std::{collections::HashSet, fs, path::{Path, PathBuf}};
+
+use crate::icons::xdg_data_dirs;
+use crate::launch::LaunchOptions;
+
+//Reads freedesktop .desktop entries, the files Linux desktops build their app menus from, so installed apps can be imported into the library.
+//See https://specifications.freedesktop.org/desktop-entry-spec/latest/
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DesktopEntry {
+    pub file : PathBuf, //The .desktop file it was read from
+    pub name : String,
+    pub program : String, //The executable from Exec, resolved through PATH when it isn't a full path
+    pub options : LaunchOptions, //The rest of Exec as arguments and environment variables, plus Path as the working directory
+    pub icon : String, //The Icon, a name to look up in the icon theme (see icons::find_theme_icon) or a path. Empty if it isn't set
+    pub groups : Vec<String>, //Catapult groups for the entry's main Categories, e.g. "Game" becomes "Games"
+    pub steam_app_id : Option<u32>, //For the shortcuts Steam makes for its games, "steam steam://rungameid/<id>"
+}
+
+const MAIN_CATEGORIES : [(&str, &str); 13] = [ //The spec's main categories and the group each one goes in, the additional categories (like "ActionGame") are too fine grained to be useful groups
+    ("AudioVideo", "Multimedia"), ("Audio", "Audio"), ("Video", "Video"), ("Development", "Development"), ("Education", "Education"),
+    ("Game", "Games"), ("Graphics", "Graphics"), ("Network", "Internet"), ("Office", "Office"), ("Science", "Science"),
+    ("Settings", "Settings"), ("System", "System"), ("Utility", "Utilities"),
+];
+
+/// Every application entry in the applications folders of the XDG data directories, sorted by name. Entries that are hidden, or that are overridden by one with the same id in a more important directory, are left out.
+pub fn scan_desktop_entries() -> Vec<DesktopEntry> {
+    let mut seen_ids : HashSet<String> = HashSet::new();
+    let mut entries = Vec::new();
+    for applications_dir in xdg_data_dirs().iter().map(|dir| dir.join("applications")) {
+        for file in desktop_files(&applications_dir) {
+            let id = file.strip_prefix(&applications_dir).unwrap_or(&file).to_string_lossy().replace('/', "-"); //The desktop file id, e.g. "kde/okular.desktop" is "kde-okular.desktop"
+            if !seen_ids.insert(id) {
+                continue; //~/.local/share/applications comes first, so the user's own copies win over the system's
+            }
+            if let Ok(text) = fs::read_to_string(&file) && let Some(entry) = parse_desktop_entry(&text, &file) {
+                entries.push(entry);
+            }
+        }
+    }
+    entries.sort_by_key(|entry| entry.name.to_lowercase());
+    entries
+}
+
+fn desktop_files(dir : &Path) -> Vec<PathBuf> { //Every .desktop file in the folder and its subfolders
+    let mut files = Vec::new();
+    for file in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = file.path();
+        if path.is_dir() {
+            files.extend(desktop_files(&path));
+        } else if path.extension().is_some_and(|extension| extension == "desktop") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Parses the text of a .desktop file. None if it isn't a visible application with something to run.
+pub fn parse_desktop_entry(text : &str, file : &Path) -> Option<DesktopEntry> {
+    let mut is_in_main_group = false;
+    let (mut name, mut exec, mut working_dir, mut icon, mut categories, mut entry_type) = (None, None, String::new(), String::new(), String::new(), String::new());
+    let mut is_hidden = false;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            is_in_main_group = line == "[Desktop Entry]"; //Other groups are actions like "New Window", which have their own Name and Exec
+            continue;
+        }
+        if !is_in_main_group || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let value = unescape_value(value.trim());
+        match key.trim() { //Localized keys like "Name[de]" don't match, so the untranslated name is used
+            "Type" => entry_type = value,
+            "Name" => name = Some(value),
+            "Exec" => exec = Some(value),
+            "Path" => working_dir = value,
+            "Icon" => icon = value,
+            "Categories" => categories = value,
+            "NoDisplay" | "Hidden" => is_hidden |= value == "true",
+            _ => {}
+        }
+    }
+    if entry_type != "Application" || is_hidden {
+        return None;
+    }
+    let name = name?;
+    let mut args = split_exec(&exec?, &name, &icon, file);
+    let mut env = Vec::new();
+    if args.first().is_some_and(|program| program == "env") { //"env FOO=bar program args", common for games that need a setting
+        args.remove(0);
+        while let Some((key, value)) = args.first().and_then(|arg| arg.split_once('=')).map(|(key, value)| (key.to_string(), value.to_string())) {
+            env.push((key, value));
+            args.remove(0);
+        }
+    }
+    if args.is_empty() {
+        return None;
+    }
+    let program = resolve_program(&args.remove(0));
+    let steam_app_id = args.iter().find_map(|arg| arg.strip_prefix("steam://rungameid/")).and_then(|app_id| app_id.parse().ok());
+    let groups = categories.split(';')
+        .filter_map(|category| MAIN_CATEGORIES.iter().find(|(main, _)| *main == category).map(|(_, group)| group.to_string()))
+        .collect();
+    Some(DesktopEntry {
+        file : file.to_path_buf(),
+        name,
+        program,
+        options : LaunchOptions { args, working_dir, env },
+        icon,
+        groups,
+        steam_app_id,
+    })
+}
+
+fn unescape_value(value : &str) -> String { //The escapes every string value can use, Exec has its own quoting on top (see split_exec)
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => { //Like "\;" in lists, left for whoever splits the value
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Splits an Exec value into the program and its arguments, following its quoting rules and filling in the field codes. Codes for files and URLs the app should open (%f, %U and so on) are dropped, since we launch apps without any.
+fn split_exec(exec : &str, name : &str, icon : &str, file : &Path) -> Vec<String> {
+    let mut args : Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut has_arg = false; //So a quoted empty string still counts as an argument
+    let mut is_quoted = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                is_quoted = !is_quoted;
+                has_arg = true;
+            }
+            '\\' if is_quoted => { //Inside quotes a backslash escapes ", `, $ and itself
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            }
+            ' ' | '\t' if !is_quoted => {
+                if has_arg || !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+                has_arg = false;
+            }
+            '%' if !is_quoted => match chars.next() {
+                Some('%') => current.push('%'),
+                Some('c') => current.push_str(name),
+                Some('k') => current.push_str(&file.to_string_lossy()),
+                Some('i') if !icon.is_empty() => { //Expands to two arguments
+                    args.push("--icon".to_string());
+                    current.push_str(icon);
+                    has_arg = true;
+                }
+                _ => {} //%f, %F, %u, %U and the deprecated codes expand to nothing
+            },
+            _ => current.push(c),
+        }
+    }
+    if has_arg || !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
+
+fn resolve_program(program : &str) -> String { //Finds a bare program name like "steam" in PATH, so the library has the real executable to track
+    if program.contains('/') {
+        return program.to_string();
+    }
+    std::env::var_os("PATH")
+        .and_then(|paths| std::env::split_paths(&paths).map(|dir| dir.join(program)).find(|path| path.is_file()))
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or(program.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(exec : &str) -> Vec<String> {
+        split_exec(exec, "My Game", "mygame", Path::new("/usr/share/applications/mygame.desktop"))
+    }
+
+    #[test]
+    fn unescapes_values() {
+        assert_eq!(unescape_value(r"a\sb\nc\td\\e"), "a b\nc\td\\e");
+        assert_eq!(unescape_value(r"Game;Action\;Shooter;"), r"Game;Action\;Shooter;"); //Left for whoever splits the list
+        assert_eq!(unescape_value("trailing\\"), "trailing\\");
+    }
+
+    #[test]
+    fn splits_quoted_arguments() {
+        assert_eq!(split(r#""/opt/my game/run" --x "a b""#), ["/opt/my game/run", "--x", "a b"]);
+        assert_eq!(split(r#"run "say \"hi\"" "\$HOME" "back\\slash""#), ["run", "say \"hi\"", "$HOME", "back\\slash"]);
+        assert_eq!(split(r#"run "" last"#), ["run", "", "last"]); //A quoted empty string is still an argument
+        assert_eq!(split("run   --spaced\t--out"), ["run", "--spaced", "--out"]);
+    }
+
+    #[test]
+    fn fills_in_field_codes() {
+        assert_eq!(split("run --progress 100%%"), ["run", "--progress", "100%"]);
+        assert_eq!(split("run --title %c"), ["run", "--title", "My Game"]);
+        assert_eq!(split("run %i"), ["run", "--icon", "mygame"]);
+        assert_eq!(split_exec("run %i", "My Game", "", Path::new("/a.desktop")), ["run"]); //No Icon, no --icon
+        assert_eq!(split("run %k"), ["run", "/usr/share/applications/mygame.desktop"]);
+        assert_eq!(split("run %f %F %u %U --last"), ["run", "--last"]); //We never launch with files or URLs
+        assert_eq!(split(r#"run "%c""#), ["run", "%c"]); //Field codes aren't expanded inside quotes
+    }
+
+    #[test]
+    fn parses_env_prefix_and_main_group() {
+        let text = "[Desktop Entry]\nType=Application\nName=My Game\nName[de]=Mein Spiel\nExec=env DXVK_HUD=1 WINEDEBUG=-all /bin/sh -c \"exec game\"\nPath=/opt/game\nCategories=Game;ActionGame;\n\n[Desktop Action Safe]\nName=Safe Mode\nExec=game --safe\n";
+        let entry = parse_desktop_entry(text, Path::new("/a.desktop")).unwrap();
+        assert_eq!(entry.name, "My Game");
+        assert_eq!(entry.program, "/bin/sh");
+        assert_eq!(entry.options.args, ["-c", "exec game"]);
+        assert_eq!(entry.options.env, [("DXVK_HUD".to_string(), "1".to_string()), ("WINEDEBUG".to_string(), "-all".to_string())]);
+        assert_eq!(entry.options.working_dir, "/opt/game");
+        assert_eq!(entry.groups, ["Games"]);
+        assert_eq!(entry.steam_app_id, None);
+    }
+
+    #[test]
+    fn reads_steam_shortcuts() {
+        let text = "[Desktop Entry]\nType=Application\nName=Dota 2\nExec=steam steam://rungameid/570\nIcon=steam_icon_570\n";
+        let entry = parse_desktop_entry(text, Path::new("/a.desktop")).unwrap();
+        assert_eq!(entry.steam_app_id, Some(570));
+        assert_eq!(entry.options.args, ["steam://rungameid/570"]);
+        assert_eq!(entry.icon, "steam_icon_570"); //Only looked up in the icon theme when it's imported
+    }
+
+    #[test]
+    fn skips_entries_that_cant_be_launched() {
+        assert!(parse_desktop_entry("[Desktop Entry]\nType=Application\nName=A\nExec=a\nNoDisplay=true\n", Path::new("/a.desktop")).is_none());
+        assert!(parse_desktop_entry("[Desktop Entry]\nType=Link\nName=A\nURL=https://example.com\n", Path::new("/a.desktop")).is_none());
+        assert!(parse_desktop_entry("[Desktop Entry]\nType=Application\nName=A\nExec=env A=1\n", Path::new("/a.desktop")).is_none());
+    }
+}
@@ -0,0 +1,95 @@
+use crate::launch::ProfileId;
+use crate::library::AppId;
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct SessionRecord { //One play session of one app
+    pub id : u64,
+    pub app : AppId,
+    pub start : u64, //Unix timestamp in milliseconds
+    pub end : u64, //Unix timestamp in milliseconds, kept up to date while the session is running
+    pub duration : u64, //Milliseconds of play time counted for this session
+    pub idle : u64, //Milliseconds the app was running but the user was away, not part of duration
+    pub exit_code : Option<i32>, //None if the app is still running, was killed by a signal, or wasn't launched by Catapult
+    pub profile_id : ProfileId, //The launch profile it was started with, which may have been removed since
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct History {
+    sessions : Vec<SessionRecord>, //Oldest first
+    next_id : u64,
+}
+
+impl History {
+    pub fn sessions(&self) -> &[SessionRecord] {
+        &self.sessions
+    }
+
+    pub fn get(&self, id : u64) -> Option<&SessionRecord> {
+        self.sessions.iter().find(|session| session.id == id)
+    }
+
+    pub fn for_app(&self, app : AppId) -> impl DoubleEndedIterator<Item = &SessionRecord> {
+        self.sessions.iter().filter(move |session| session.app == app)
+    }
+
+    /// Records the start of a session and returns its id.
+    pub fn start(&mut self, app : AppId, profile_id : ProfileId, start : u64) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.sessions.push(SessionRecord {
+            id,
+            app,
+            start,
+            end : start,
+            profile_id,
+            ..Default::default()
+        });
+        id
+    }
+
+    /// Adds play time and idle time to a running session and moves its end up to `now`.
+    pub fn add_time(&mut self, id : u64, millis : u64, idle : u64, now : u64) {
+        if let Some(session) = self.get_mut(id) {
+            session.duration += millis;
+            session.idle += idle;
+            session.end = now;
+        }
+    }
+
+    pub fn finish(&mut self, id : u64, now : u64, exit_code : Option<i32>) {
+        if let Some(session) = self.get_mut(id) {
+            session.end = now;
+            session.exit_code = exit_code;
+        }
+    }
+
+    pub fn set_profile(&mut self, id : u64, profile_id : ProfileId) {
+        if let Some(session) = self.get_mut(id) {
+            session.profile_id = profile_id;
+        }
+    }
+
+    pub fn remove(&mut self, id : u64) -> Option<SessionRecord> {
+        let index = self.sessions.iter().position(|session| session.id == id)?;
+        Some(self.sessions.remove(index))
+    }
+
+    /// Replaces a session's duration, returns the old one so the app's play time can be corrected.
+    pub fn set_duration(&mut self, id : u64, duration : u64) -> Option<u64> {
+        let session = self.get_mut(id)?;
+        let old_duration = session.duration;
+        session.duration = duration;
+        session.end = session.end.max(session.start + duration);
+        Some(old_duration)
+    }
+
+    pub fn remove_app(&mut self, app : AppId) { //Forget every session of an app that was removed from the library
+        self.sessions.retain(|session| session.app != app);
+    }
+
+    fn get_mut(&mut self, id : u64) -> Option<&mut SessionRecord> {
+        self.sessions.iter_mut().find(|session| session.id == id)
+    }
+}
@@ -0,0 +1,361 @@
+use std::{collections::{HashSet, hash_map::DefaultHasher}, fmt, fs, hash::{Hash, Hasher}, path::{Path, PathBuf}, sync::{Arc, Mutex, mpsc::{self, Receiver, Sender}}, thread, time::UNIX_EPOCH};
+use egui::ColorImage;
+#[cfg(not(target_os = "linux"))]
+use file_icon_provider::get_file_icon;
+use image::RgbaImage;
+
+use crate::app::APP_NAME;
+use crate::artwork::load_image;
+#[cfg(target_os = "linux")]
+use crate::artwork::{is_svg, render_svg};
+use crate::textures::TextureTier;
+
+//Executable icons. Extracting them is slow, so it happens on a pool of background threads, and every icon is kept as a PNG in a cache directory so the next start only has to read the file.
+//The same threads decode artwork, a grid full of covers would otherwise stall the UI for as long as it takes to decode them all.
+//Cache files are named after the executable's path and modification time, so rebuilding or updating an app makes us extract its icon again.
+
+const MAX_WORKERS : usize = 4; //Opening a big group asks for a lot of icons at once, but the disk is the bottleneck past a few threads
+const MAX_ICON_SIZE : u32 = 256; //The biggest icons Windows stores, asking for more just scales them up
+
+enum Source { //Where a requested image comes from
+    Executable, //The icon of the executable at the request's key
+    File(PathBuf), //An image file like custom artwork, scaled down to fit the tier
+}
+
+/// Loads icons and artwork on a pool of worker threads, waking the UI up when each one is ready.
+pub struct IconLoader {
+    requests : Sender<(String, Source, TextureTier)>,
+    results : Receiver<(String, TextureTier, Option<ColorImage>)>,
+    pending : HashSet<(String, TextureTier)>, //Images that have been asked for but haven't come back yet, so each one is only asked for once
+}
+
+impl IconLoader {
+    pub fn spawn(ctx : egui::Context) -> Self {
+        let (requests, request_receiver) = mpsc::channel::<(String, Source, TextureTier)>();
+        let request_receiver = Arc::new(Mutex::new(request_receiver)); //Shared so whichever worker is free takes the next request
+        let (result_sender, results) = mpsc::channel();
+        let worker_count = thread::available_parallelism().map(|count| count.get()).unwrap_or(1).min(MAX_WORKERS);
+        for _ in 0..worker_count {
+            let request_receiver = Arc::clone(&request_receiver);
+            let result_sender = result_sender.clone();
+            let ctx = ctx.clone();
+            thread::spawn(move || loop { //Ends once the IconLoader is dropped and the channel closes
+                let Ok(Ok((key, source, tier))) = request_receiver.lock().map(|receiver| receiver.recv()) else { break }; //The lock is only held while waiting, not while extracting
+                let image = match source {
+                    Source::Executable => Some(load_icon(&key, tier)),
+                    Source::File(path) => load_image(&path, tier.pixels()),
+                };
+                if result_sender.send((key, tier, image)).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            });
+        }
+        Self { requests, results, pending : HashSet::new() }
+    }
+
+    /// Asks for the icon of the executable at `path`, which comes back keyed by the path.
+    pub fn request(&mut self, path : &str, tier : TextureTier) {
+        self.send(path, Source::Executable, tier);
+    }
+
+    /// Asks for the image file at `path`, which comes back under `key`, or as None if it can't be decoded.
+    pub fn request_image(&mut self, key : &str, path : &Path, tier : TextureTier) {
+        self.send(key, Source::File(path.to_path_buf()), tier);
+    }
+
+    fn send(&mut self, key : &str, source : Source, tier : TextureTier) {
+        if self.pending.insert((key.to_string(), tier)) {
+            let _ = self.requests.send((key.to_string(), source, tier));
+        }
+    }
+
+    /// The images that have finished loading since the last call, as (key, tier, image). Executable icons are never None, they fall back to missing_icon.
+    pub fn loaded(&mut self) -> Vec<(String, TextureTier, Option<ColorImage>)> {
+        let loaded : Vec<(String, TextureTier, Option<ColorImage>)> = self.results.try_iter().collect();
+        for (path, tier, _) in loaded.iter() {
+            self.pending.remove(&(path.clone(), *tier));
+        }
+        loaded
+    }
+}
+
+/// The icon shown while the real one is loading, and for executables we can't get an icon from at all.
+pub fn missing_icon() -> ColorImage {
+    let image = image::load_from_memory(include_bytes!("../assets/missing.png")).map(|image| image.to_rgba8()).unwrap_or_default();
+    ColorImage::from_rgba_unmultiplied([image.width() as usize, image.height() as usize], image.as_raw())
+}
+
+fn load_icon(exe_path : &str, tier : TextureTier) -> ColorImage { //Reads the icon from the cache, extracting and caching it if it isn't there
+    let size = tier.pixels().min(MAX_ICON_SIZE);
+    let cache_file = cache_file(exe_path, size);
+    if let Some(icon) = cache_file.as_deref().and_then(read_cached_icon) {
+        return icon;
+    }
+    match get_color_icon(exe_path, size as u16) {
+        Ok(icon) => {
+            if let Some(cache_file) = cache_file {
+                write_cached_icon(&cache_file, &icon);
+            }
+            icon
+        }
+        Err(_) => missing_icon(), //Not cached, the executable might have an icon we can read next time (e.g. it was missing and has been reinstalled)
+    }
+}
+
+fn icon_cache_dir() -> Option<PathBuf> {
+    eframe::storage_dir(APP_NAME).map(|dir| dir.join("icon_cache"))
+}
+
+fn path_hash(exe_path : &str) -> u64 { //Only has to be stable between runs of the same build, a different one just means extracting the icons again
+    let mut hasher = DefaultHasher::new();
+    exe_path.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn cache_file(exe_path : &str, size : u32) -> Option<PathBuf> { //"{path hash}_{modified time}_{size}.png", None if the executable can't be read
+    let modified = fs::metadata(exe_path).and_then(|metadata| metadata.modified()).ok()?;
+    let modified = modified.duration_since(UNIX_EPOCH).ok()?.as_millis();
+    Some(icon_cache_dir()?.join(format!("{:016x}_{}_{}.png", path_hash(exe_path), modified, size)))
+}
+
+fn read_cached_icon(cache_file : &Path) -> Option<ColorImage> {
+    let image = image::open(cache_file).ok()?.to_rgba8();
+    Some(ColorImage::from_rgba_premultiplied([image.width() as usize, image.height() as usize], image.as_raw()))
+}
+
+fn write_cached_icon(cache_file : &Path, icon : &ColorImage) { //Also deletes icons cached for older versions of the same executable
+    let Some(dir) = cache_file.parent() else { return };
+    let Some(name) = cache_file.file_name().and_then(|name| name.to_str()) else { return };
+    let mut parts = name.split('_');
+    let (Some(hash), Some(modified)) = (parts.next(), parts.next()) else { return };
+    let (same_executable, same_version) = (format!("{}_", hash), format!("{}_{}_", hash, modified));
+    if fs::create_dir_all(dir).is_err() {
+        return;
+    }
+    for old_file in fs::read_dir(dir).into_iter().flatten().flatten() {
+        if old_file.file_name().to_str().is_some_and(|name| name.starts_with(&same_executable) && !name.starts_with(&same_version)) { //Other sizes of the same version are still good
+            let _ = fs::remove_file(old_file.path());
+        }
+    }
+    let bytes : Vec<u8> = icon.pixels.iter().flat_map(|pixel| pixel.to_array()).collect();
+    if let Some(image) = RgbaImage::from_raw(icon.width() as u32, icon.height() as u32, bytes) {
+        let _ = image.save(cache_file);
+    }
+}
+
+#[derive(Debug)]
+pub enum IconError {
+    #[cfg(not(target_os = "linux"))]
+    Extract(file_icon_provider::Error), //The file has no icon we can read, e.g. a script, a shortcut, a deleted executable or a binary for another OS
+    #[cfg(not(target_os = "linux"))]
+    BadPixels { width : u32, height : u32, len : usize }, //The icon's pixels don't add up to its size
+    #[cfg(target_os = "linux")]
+    NotFound, //There's no image next to the executable or in the icon theme
+    #[cfg(target_os = "linux")]
+    Decode(image::ImageError),
+    #[cfg(target_os = "linux")]
+    BadSvg, //An SVG from the icon theme that we couldn't draw
+}
+
+impl fmt::Display for IconError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(not(target_os = "linux"))]
+            IconError::Extract(error) => write!(f, "Couldn't get the file's icon: {}", error),
+            #[cfg(not(target_os = "linux"))]
+            IconError::BadPixels { width, height, len } => write!(f, "The icon is {}x{} but has {} bytes of pixels", width, height, len),
+            #[cfg(target_os = "linux")]
+            IconError::NotFound => write!(f, "Couldn't find an icon for the file"),
+            #[cfg(target_os = "linux")]
+            IconError::Decode(error) => write!(f, "Couldn't read the icon: {}", error),
+            #[cfg(target_os = "linux")]
+            IconError::BadSvg => write!(f, "Couldn't draw the icon's SVG"),
+        }
+    }
+}
+
+impl std::error::Error for IconError {}
+
+/// Loads the file icon for the given executable path, asking for it at `size` pixels, and converts it to an egui ColorImage, which can then be loaded as a texture and displayed in the UI. The image is whatever size the icon actually came back at.
+#[cfg(not(target_os = "linux"))]
+pub fn get_color_icon(exe_path : &str, size : u16) -> Result<ColorImage, IconError> {
+    let app_icon = get_file_icon(exe_path, size).map_err(IconError::Extract)?;
+    let len = app_icon.pixels.len();
+    let app_icon_image = RgbaImage::from_raw(app_icon.width, app_icon.height, app_icon.pixels)
+        .ok_or(IconError::BadPixels { width : app_icon.width, height : app_icon.height, len })?;
+    Ok(ColorImage::from_rgba_premultiplied([app_icon_image.width() as usize, app_icon_image.height() as usize], app_icon_image.as_raw()))
+}
+
+/// Linux executables don't carry icons, so this looks for one the way a file manager would: an image next to the executable named after it (or just "icon.png"), then the icon theme entry with the executable's name. Scaled down to at most `size` pixels, SVGs are drawn at `size`.
+#[cfg(target_os = "linux")]
+pub fn get_color_icon(exe_path : &str, size : u16) -> Result<ColorImage, IconError> {
+    let path = find_executable_icon(Path::new(exe_path), size as u32).ok_or(IconError::NotFound)?;
+    if is_svg(&path) {
+        return render_svg(&path, size as u32).ok_or(IconError::BadSvg);
+    }
+    let mut image = image::open(path).map_err(IconError::Decode)?;
+    if image.width() > size as u32 || image.height() > size as u32 {
+        image = image.resize(size as u32, size as u32, image::imageops::FilterType::Triangle);
+    }
+    let image = image.to_rgba8();
+    Ok(ColorImage::from_rgba_unmultiplied([image.width() as usize, image.height() as usize], image.as_raw()))
+}
+
+#[cfg(target_os = "linux")]
+fn find_executable_icon(exe_path : &Path, size : u32) -> Option<PathBuf> {
+    let name = exe_path.file_stem()?.to_str()?;
+    let dir = exe_path.parent()?;
+    [format!("{}.png", name), "icon.png".to_string()].iter()
+        .map(|file_name| dir.join(file_name))
+        .find(|path| path.is_file())
+        .or_else(|| find_theme_icon(name, size))
+        .or_else(|| find_theme_icon(&name.to_lowercase(), size))
+}
+
+//An icon theme as find_theme_icon searches it: the folders it's in (a theme can be spread across ~/.icons and several data directories) and its subfolders of application icons
+#[cfg(target_os = "linux")]
+struct IconTheme {
+    dirs : Vec<PathBuf>,
+    subdirs : Vec<(String, Option<u32>)>, //Relative to each of dirs, with the icon size they hold. None for scalable folders, which hold SVGs that can be drawn at any size
+}
+
+#[cfg(target_os = "linux")]
+static ICON_THEMES : std::sync::OnceLock<Vec<IconTheme>> = std::sync::OnceLock::new(); //Read once, theme changes are picked up on the next start
+
+/// Finds an icon by its freedesktop icon name, in the user's icon theme and the themes it inherits from, then in hicolor (the theme every desktop falls back to), then in pixmaps. Prefers the smallest size that's at least `size`, then a scalable SVG, then the biggest smaller one. Names that are already absolute paths are used as they are.
+#[cfg(target_os = "linux")]
+pub fn find_theme_icon(name : &str, size : u32) -> Option<PathBuf> {
+    if Path::new(name).is_absolute() {
+        return Path::new(name).is_file().then(|| PathBuf::from(name));
+    }
+    for theme in ICON_THEMES.get_or_init(read_icon_themes) {
+        let mut subdirs : Vec<&(String, Option<u32>)> = theme.subdirs.iter().collect();
+        subdirs.sort_by_key(|(_, subdir_size)| match subdir_size { //Big enough first, then scalable, then as close as possible
+            Some(subdir_size) if *subdir_size >= size => (0, subdir_size - size),
+            None => (1, 0),
+            Some(subdir_size) => (2, size - subdir_size),
+        });
+        for (subdir, _) in subdirs {
+            for dir in theme.dirs.iter() {
+                for extension in ["png", "svg"] { //Some themes keep SVGs in their fixed size folders too
+                    let path = dir.join(subdir).join(format!("{}.{}", name, extension));
+                    if path.is_file() {
+                        return Some(path);
+                    }
+                }
+            }
+        }
+    }
+    xdg_data_dirs().iter()
+        .flat_map(|dir| ["png", "svg"].map(|extension| dir.join("pixmaps").join(format!("{}.{}", name, extension))))
+        .find(|path| path.is_file())
+}
+
+#[cfg(target_os = "linux")]
+fn read_icon_themes() -> Vec<IconTheme> { //The user's theme, the themes it inherits from in order, then hicolor
+    let base_dirs : Vec<PathBuf> = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".icons")).into_iter()
+        .chain(xdg_data_dirs().iter().map(|dir| dir.join("icons")))
+        .collect();
+    let mut themes : Vec<IconTheme> = Vec::new();
+    let mut names : Vec<String> = current_icon_theme().into_iter().filter(|name| name != "hicolor").collect();
+    let mut index = 0;
+    while index < names.len() {
+        let Some((theme, parents)) = read_icon_theme(&base_dirs, &names[index]) else {
+            index += 1;
+            continue;
+        };
+        themes.push(theme);
+        index += 1;
+        let new_parents : Vec<String> = parents.into_iter().filter(|parent| parent != "hicolor" && !names.contains(parent)).collect();
+        for (offset, parent) in new_parents.into_iter().enumerate() { //Before the parents of later parents
+            names.insert(index + offset, parent);
+        }
+    }
+    themes.extend(read_icon_theme(&base_dirs, "hicolor").map(|(theme, _)| theme)); //Always last, whatever the themes say they inherit
+    themes
+}
+
+#[cfg(target_os = "linux")]
+fn read_icon_theme(base_dirs : &[PathBuf], name : &str) -> Option<(IconTheme, Vec<String>)> { //The theme and the names of the themes it inherits from, None if it isn't installed
+    let dirs : Vec<PathBuf> = base_dirs.iter().map(|dir| dir.join(name)).filter(|dir| dir.is_dir()).collect();
+    if dirs.is_empty() {
+        return None;
+    }
+    let Some(theme_index) = dirs.iter().find_map(|dir| fs::read_to_string(dir.join("index.theme")).ok()) else { //Some minimal systems have hicolor's folders without its index
+        let subdirs = [16, 22, 24, 32, 48, 64, 96, 128, 256, 512].iter().map(|size| (format!("{}x{}/apps", size, size), Some(*size)))
+            .chain(std::iter::once(("scalable/apps".to_string(), None)))
+            .collect();
+        return Some((IconTheme { dirs, subdirs }, Vec::new()));
+    };
+    let parents = ini_value(&theme_index, "Icon Theme", "Inherits").unwrap_or_default().split(',').map(str::trim)
+        .filter(|parent| !parent.is_empty())
+        .map(str::to_string)
+        .collect();
+    let subdirs = ini_value(&theme_index, "Icon Theme", "Directories").unwrap_or_default().split(',').map(str::trim)
+        .filter(|subdir| !subdir.is_empty())
+        .filter(|subdir| ini_value(&theme_index, subdir, "Context").map_or(subdir.contains("apps"), |context| context == "Applications"))
+        .map(|subdir| {
+            let is_scalable = ini_value(&theme_index, subdir, "Type").is_some_and(|subdir_type| subdir_type == "Scalable");
+            let subdir_size = ini_value(&theme_index, subdir, "Size").and_then(|subdir_size| subdir_size.parse().ok()).unwrap_or(48);
+            (subdir.to_string(), if is_scalable { None } else { Some(subdir_size) })
+        })
+        .collect();
+    Some((IconTheme { dirs, subdirs }, parents))
+}
+
+#[cfg(target_os = "linux")]
+fn current_icon_theme() -> Option<String> { //Each desktop keeps this somewhere different, so try KDE's config on KDE and GNOME's settings (then GTK's config files) everywhere else
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()).map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    let from_config = |file : &str, section : &str, key : &str| fs::read_to_string(config_dir.join(file)).ok().and_then(|text| ini_value(&text, section, key));
+    let from_kde = || from_config("kdeglobals", "Icons", "Theme");
+    let from_gnome = || std::process::Command::new("gsettings").args(["get", "org.gnome.desktop.interface", "icon-theme"]).output().ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().trim_matches('\'').to_string())
+        .filter(|theme| !theme.is_empty());
+    let from_gtk = || from_config("gtk-4.0/settings.ini", "Settings", "gtk-icon-theme-name").or_else(|| from_config("gtk-3.0/settings.ini", "Settings", "gtk-icon-theme-name"));
+    let is_kde = std::env::var("XDG_CURRENT_DESKTOP").is_ok_and(|desktop| desktop.contains("KDE"));
+    if is_kde { from_kde().or_else(from_gnome).or_else(from_gtk) } else { from_gnome().or_else(from_gtk).or_else(from_kde) }
+}
+
+#[cfg(target_os = "linux")]
+fn ini_value(text : &str, section : &str, key : &str) -> Option<String> { //The value of `key` in `[section]` of an ini style file like index.theme
+    let mut current_section = "";
+    for line in text.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            current_section = name;
+        } else if current_section == section && let Some((line_key, value)) = line.split_once('=') && line_key.trim() == key {
+            return Some(value.trim().to_string());
+        }
+    }
+    None
+}
+
+/// Where freedesktop data like icons and .desktop files lives, most important first: $XDG_DATA_HOME (~/.local/share) then $XDG_DATA_DIRS (/usr/local/share and /usr/share).
+#[cfg(target_os = "linux")]
+pub fn xdg_data_dirs() -> Vec<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()).map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+    let data_dirs = std::env::var("XDG_DATA_DIRS").ok().filter(|dirs| !dirs.is_empty()).unwrap_or("/usr/local/share:/usr/share".to_string());
+    data_home.into_iter().chain(data_dirs.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from)).collect()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_theme_directories_and_parents() {
+        let base = std::env::temp_dir().join(format!("catapult_icon_theme_test_{}", std::process::id()));
+        let theme = base.join("Papirus");
+        fs::create_dir_all(&theme).unwrap();
+        fs::write(theme.join("index.theme"), "[Icon Theme]\nName=Papirus\nInherits=breeze,hicolor\nDirectories=48x48/apps,48x48/devices,64x64/apps,symbolic/apps\n\n[48x48/apps]\nContext=Applications\nSize=48\n\n[48x48/devices]\nContext=Devices\nSize=48\n\n[64x64/apps]\nSize=64\n\n[symbolic/apps]\nContext=Applications\nSize=16\nType=Scalable\n").unwrap();
+        let (read, parents) = read_icon_theme(std::slice::from_ref(&base), "Papirus").unwrap();
+        assert_eq!(parents, ["breeze", "hicolor"]);
+        assert_eq!(read.dirs, [theme]);
+        assert_eq!(read.subdirs, [("48x48/apps".to_string(), Some(48)), ("64x64/apps".to_string(), Some(64)), ("symbolic/apps".to_string(), None)]);
+        assert!(read_icon_theme(std::slice::from_ref(&base), "Missing").is_none());
+        fs::remove_dir_all(base).unwrap();
+    }
+}
@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+//Works out how long it's been since the user last touched the mouse or keyboard anywhere on the system, not just in Catapult's window. Gamepads don't count, GetLastInputInfo doesn't see XInput
+
+#[cfg(windows)]
+pub fn input_idle_time() -> Option<Duration> {
+    #[repr(C)]
+    struct LastInputInfo {
+        cb_size : u32,
+        dw_time : u32,
+    }
+
+    #[link(name = "user32")]
+    unsafe extern "system" {
+        fn GetLastInputInfo(plii : *mut LastInputInfo) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetTickCount() -> u32;
+    }
+
+    let mut info = LastInputInfo { cb_size : std::mem::size_of::<LastInputInfo>() as u32, dw_time : 0 };
+    //SAFETY: info is a valid LASTINPUTINFO with cbSize set, which is all GetLastInputInfo needs
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+    //SAFETY: GetTickCount has no preconditions
+    let now = unsafe { GetTickCount() };
+    Some(Duration::from_millis(now.wrapping_sub(info.dw_time) as u64)) //Both wrap around every 49.7 days, wrapping_sub keeps the difference right
+}
+
+#[cfg(not(windows))]
+pub fn input_idle_time() -> Option<Duration> { //No portable way to ask without talking to the display server, so only the CPU check works here
+    None
+}
+
+pub fn is_input_idle_supported() -> bool {
+    cfg!(windows)
+}
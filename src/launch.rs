@@ -0,0 +1,81 @@
+use std::{io, path::{Path, PathBuf}, process::{Child, Command}};
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct LaunchOptions {
+    pub args : Vec<String>, //Passed to the executable one by one, so arguments with spaces don't need quoting
+    pub working_dir : String, //Empty means the folder the executable is in (see working_dir)
+    pub env : Vec<(String, String)>, //Environment variables set on top of Catapult's own, e.g. DXVK_HUD or SteamAppId
+}
+
+pub type ProfileId = u64; //Unique among one app's profiles and never reused, so the history can point at a profile through renames and removals
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct LaunchProfile { //One way of starting an app, e.g. DX11 vs Vulkan or modded vs vanilla
+    pub id : ProfileId,
+    pub name : String, //Shown in the UI, doesn't have to be unique
+    pub path : String, //The executable to run, empty means the app's own executable
+    pub options : LaunchOptions,
+    pub play_time : u64, //How much of the app's total play time (in milliseconds) was spent in this profile
+}
+
+impl LaunchProfile {
+    pub fn new(id : ProfileId, name : &str) -> Self {
+        Self {
+            id,
+            name : name.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The folder the app should be started in, either the one the user picked or the folder the executable lives in.
+pub fn working_dir(path : &str, options : &LaunchOptions) -> Option<PathBuf> {
+    if !options.working_dir.is_empty() {
+        return Some(PathBuf::from(&options.working_dir));
+    }
+    Path::new(path).parent().filter(|parent| !parent.as_os_str().is_empty()).map(Path::to_path_buf)
+}
+
+fn build_command(path : &str, options : &LaunchOptions) -> Command {
+    let mut command = Command::new(path);
+    command.args(&options.args);
+    if let Some(dir) = working_dir(path, options) {
+        command.current_dir(dir);
+    }
+    for (key, value) in options.env.iter().filter(|(key, _)| !key.is_empty()) { //Half filled rows in the Edit App window have no key yet
+        command.env(key, value);
+    }
+    command
+}
+
+/// Opens the executable with the given options. The child is handed to the tracker, which waits on it for the exit code.
+pub fn open_app(path : &str, options : &LaunchOptions) -> io::Result<Child>{
+    let mut command = build_command(path, options);
+    command.spawn()
+}
+
+/// Opens a URL like steam://rungameid/<id> with whatever program handles it. There's no child to hand to the tracker, the program it opens starts the app for us.
+pub fn open_url(url : &str) -> io::Result<()> {
+    open::that_detached(url)
+}
+
+/// Turns the error from open_app into something a user can act on.
+pub fn describe_launch_error(path : &str, error : &io::Error) -> String {
+    let reason = match error.kind() {
+        io::ErrorKind::NotFound => "the file doesn't exist (was it moved or deleted?)".to_string(),
+        io::ErrorKind::PermissionDenied => "permission denied".to_string(),
+        _ if is_not_executable(error) => "it isn't an executable".to_string(),
+        _ => error.to_string(),
+    };
+    format!("Couldn't launch {}: {}", path, reason)
+}
+
+fn is_not_executable(error : &io::Error) -> bool {
+    #[cfg(windows)]
+    const NOT_EXECUTABLE : i32 = 193; //ERROR_BAD_EXE_FORMAT
+    #[cfg(not(windows))]
+    const NOT_EXECUTABLE : i32 = 8; //ENOEXEC
+    error.raw_os_error() == Some(NOT_EXECUTABLE)
+}
@@ -1,3 +1,20 @@
 mod app;
+mod artwork;
+mod dates;
+#[cfg(target_os = "linux")]
+mod desktop;
+mod history;
+mod icons;
+mod idle;
+mod launch;
+mod library;
+mod migration;
+mod notifications;
+mod search;
+mod settings;
+mod stats;
+mod steam;
+mod textures;
+mod tracker;
 pub use app::CatapultApp;
 //I have no fucking clue why this file is here, the eframe template just seemed to have this, so I'm too scared to remove it.
\ No newline at end of file
@@ -0,0 +1,496 @@
+use std::path::Path;
+
+use crate::dates::now_millis;
+use crate::launch::{LaunchProfile, ProfileId};
+
+//The headless model of the user's app library. Everything the UI shows about an app (its path, name, play time and groups) lives on one AppEntry, so nothing can drift out of sync between parallel maps.
+
+pub type AppId = u64; //Generated when an app is added and never changes, so moving an executable doesn't orphan its data
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AppEntry {
+    pub id : AppId,
+    pub path : String, //Path to the executable, can be changed by the user at any time
+    pub name : String, //The name shown in the UI, defaults to the executable name (see get_executable_name)
+    pub play_time : u64, //Total time played in milliseconds, across every profile
+    pub groups : Vec<String>, //Names of the groups this app is in
+    pub profiles : Vec<LaunchProfile>, //The different ways this app can be launched, there is always at least one
+    pub default_profile : usize, //Index into profiles used by the "LAUNCH >" button
+    pub next_profile_id : ProfileId, //The id the next added profile will get
+    pub tracked_process_name : String, //For apps started through a launcher stub, the real game's process name, which keeps the session alive while it runs
+    pub tracked_dir : String, //Any process running an executable from inside this folder keeps the session alive, for games started through a launcher like Steam
+    pub steam_app_id : u32, //Launches the app through Steam instead of running the executable, 0 for apps that aren't Steam games
+    pub tags : Vec<String>, //Free form labels the user can search by, unlike groups they don't get their own list
+    pub added_at : u64, //Unix timestamp in milliseconds of when the app was added, 0 for apps added before this was recorded
+    pub artwork : Artwork, //Custom images shown instead of the executable's icon
+}
+
+impl Default for AppEntry {
+    fn default() -> Self {
+        Self {
+            id : 0,
+            path : "".to_string(),
+            name : "".to_string(),
+            play_time : 0,
+            groups : Vec::new(),
+            profiles : Vec::new(),
+            default_profile : 0,
+            next_profile_id : 0,
+            tracked_process_name : "".to_string(),
+            tracked_dir : "".to_string(),
+            steam_app_id : 0,
+            tags : Vec::new(),
+            added_at : 0,
+            artwork : Artwork::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ArtworkKind {
+    Icon, //Shown in the app lists
+    Cover, //Shown on the grid and the app's page
+    Background, //Fills the central panel behind the app's page
+    Logo, //Shown instead of the app's name on its page
+}
+
+impl ArtworkKind {
+    pub const ALL : [ArtworkKind; 4] = [ArtworkKind::Icon, ArtworkKind::Cover, ArtworkKind::Background, ArtworkKind::Logo];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ArtworkKind::Icon => "Icon",
+            ArtworkKind::Cover => "Cover",
+            ArtworkKind::Background => "Background",
+            ArtworkKind::Logo => "Logo",
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Artwork { //File names inside the artwork directory (see artwork::artwork_dir), empty when the user hasn't picked an image
+    pub icon : String,
+    pub cover : String,
+    pub background : String,
+    pub logo : String,
+}
+
+impl Artwork {
+    pub fn get(&self, kind : ArtworkKind) -> &str {
+        match kind {
+            ArtworkKind::Icon => &self.icon,
+            ArtworkKind::Cover => &self.cover,
+            ArtworkKind::Background => &self.background,
+            ArtworkKind::Logo => &self.logo,
+        }
+    }
+
+    fn get_mut(&mut self, kind : ArtworkKind) -> &mut String {
+        match kind {
+            ArtworkKind::Icon => &mut self.icon,
+            ArtworkKind::Cover => &mut self.cover,
+            ArtworkKind::Background => &mut self.background,
+            ArtworkKind::Logo => &mut self.logo,
+        }
+    }
+}
+
+impl AppEntry {
+    pub fn profile(&self, index : usize) -> Option<&LaunchProfile> {
+        self.profiles.get(index)
+    }
+
+    pub fn profile_by_id(&self, profile_id : ProfileId) -> Option<&LaunchProfile> {
+        self.profiles.iter().find(|profile| profile.id == profile_id)
+    }
+
+    pub fn default_profile_id(&self) -> ProfileId {
+        self.profile(self.default_profile_index()).map(|profile| profile.id).unwrap_or_default()
+    }
+
+    /// Index of the profile "LAUNCH >" uses, falling back to the first profile if the default was removed.
+    pub fn default_profile_index(&self) -> usize {
+        if self.default_profile < self.profiles.len() { self.default_profile } else { 0 }
+    }
+
+    fn generate_profile_id(&mut self) -> ProfileId {
+        self.next_profile_id = self.next_profile_id.max(self.profiles.iter().map(|profile| profile.id + 1).max().unwrap_or(0)); //Same as Library::generate_id
+        let id = self.next_profile_id;
+        self.next_profile_id += 1;
+        id
+    }
+
+    /// A "Profile N" name none of the app's profiles have yet, for the "Add Profile" button.
+    pub fn unused_profile_name(&self) -> String {
+        let is_used = |name : &str| self.profiles.iter().any(|profile| profile.name == name);
+        (self.profiles.len() + 1..).map(|number| format!("Profile {}", number)).find(|name| !is_used(name)).unwrap_or_default()
+    }
+
+    /// The executable a profile runs, profiles without their own path run the app's executable.
+    pub fn profile_path<'a>(&'a self, profile : &'a LaunchProfile) -> &'a str {
+        if profile.path.is_empty() { &self.path } else { &profile.path }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Library {
+    entries : Vec<AppEntry>, //All the apps the user has added, kept sorted by name
+    group_names : Vec<String>, //The groups the user has created, in the order they were created
+    next_id : AppId, //The id the next added app will get
+}
+
+impl Library {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn entries(&self) -> &[AppEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, id : AppId) -> Option<&AppEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    pub fn get_mut(&mut self, id : AppId) -> Option<&mut AppEntry> {
+        self.entries.iter_mut().find(|entry| entry.id == id)
+    }
+
+    pub fn find_by_path(&self, path : &str) -> Option<&AppEntry> {
+        self.entries.iter().find(|entry| entry.path == path)
+    }
+
+    /// Adds the executable at `path` to the library and returns its id. If the path is already in the library, the existing entry is renamed instead.
+    pub fn add(&mut self, path : &str, name : &str) -> AppId {
+        if let Some(id) = self.find_by_path(path).map(|entry| entry.id) {
+            self.rename(id, name);
+            return id;
+        }
+        self.add_new(path, name)
+    }
+
+    /// Adds a new entry even if another app already uses `path`, for programs that start many different apps, like steam, flatpak or python.
+    pub fn add_new(&mut self, path : &str, name : &str) -> AppId {
+        let id = self.generate_id();
+        self.entries.push(AppEntry {
+            id,
+            path : path.to_string(),
+            name : display_name(path, name),
+            profiles : vec![LaunchProfile::new(0, DEFAULT_PROFILE_NAME)],
+            next_profile_id : 1,
+            added_at : now_millis(),
+            ..Default::default()
+        });
+        self.sort();
+        id
+    }
+
+    pub fn remove(&mut self, id : AppId) -> Option<AppEntry> {
+        let index = self.entries.iter().position(|entry| entry.id == id)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Renames the app, an empty name resets it back to the executable name.
+    pub fn rename(&mut self, id : AppId, name : &str) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.name = display_name(&entry.path, name);
+            self.sort();
+        }
+    }
+
+    /// Points the app at a new executable, keeping its name, play time and groups.
+    pub fn set_path(&mut self, id : AppId, path : &str) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.path = path.to_string();
+        }
+    }
+
+    pub fn set_tracked_process_name(&mut self, id : AppId, process_name : &str) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.tracked_process_name = process_name.to_string();
+        }
+    }
+
+    pub fn set_tracked_dir(&mut self, id : AppId, dir : &str) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.tracked_dir = dir.to_string();
+        }
+    }
+
+    pub fn set_steam_app_id(&mut self, id : AppId, app_id : u32) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.steam_app_id = app_id;
+        }
+    }
+
+    /// Replaces the app's tags, dropping blank and repeated ones.
+    pub fn set_tags(&mut self, id : AppId, tags : &[String]) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.tags.clear();
+            for tag in tags.iter().map(|tag| tag.trim()).filter(|tag| !tag.is_empty()) {
+                if !entry.tags.iter().any(|existing| existing.eq_ignore_ascii_case(tag)) {
+                    entry.tags.push(tag.to_string());
+                }
+            }
+        }
+    }
+
+    /// Sets one of the app's custom images, an empty file name unsets it. Returns the file name it replaced so the old image can be deleted.
+    pub fn set_artwork(&mut self, id : AppId, kind : ArtworkKind, file_name : &str) -> Option<String> {
+        let entry = self.get_mut(id)?;
+        Some(std::mem::replace(entry.artwork.get_mut(kind), file_name.to_string()))
+    }
+
+    /// Adds a new profile with the given name and returns its index.
+    pub fn add_profile(&mut self, id : AppId, name : &str) -> Option<usize> {
+        let entry = self.get_mut(id)?;
+        let profile_id = entry.generate_profile_id();
+        entry.profiles.push(LaunchProfile::new(profile_id, name));
+        Some(entry.profiles.len() - 1)
+    }
+
+    /// Removes a profile, the last profile of an app can't be removed. Its play time stays in the app's total.
+    pub fn remove_profile(&mut self, id : AppId, index : usize) {
+        if let Some(entry) = self.get_mut(id) && entry.profiles.len() > 1 && index < entry.profiles.len() {
+            entry.profiles.remove(index);
+            if entry.default_profile > index || entry.default_profile >= entry.profiles.len() { //Keep pointing at the same profile it did before
+                entry.default_profile = entry.default_profile.saturating_sub(1);
+            }
+        }
+    }
+
+    pub fn set_profile(&mut self, id : AppId, index : usize, profile : LaunchProfile) {
+        if let Some(entry) = self.get_mut(id) && let Some(old_profile) = entry.profiles.get_mut(index) {
+            *old_profile = profile;
+        }
+    }
+
+    pub fn set_default_profile(&mut self, id : AppId, index : usize) {
+        if let Some(entry) = self.get_mut(id) && index < entry.profiles.len() {
+            entry.default_profile = index;
+        }
+    }
+
+    /// Adds to both the app's total and the breakdown for the profile it was launched with.
+    pub fn add_play_time(&mut self, id : AppId, profile_id : ProfileId, millis : u64) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.play_time += millis;
+            if let Some(profile) = entry.profiles.iter_mut().find(|profile| profile.id == profile_id) {
+                profile.play_time += millis;
+            }
+        }
+    }
+
+    /// Takes play time back off the app and the profile, used when a session in the history is deleted or shortened.
+    pub fn remove_play_time(&mut self, id : AppId, profile_id : ProfileId, millis : u64) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.play_time = entry.play_time.saturating_sub(millis);
+            if let Some(profile) = entry.profiles.iter_mut().find(|profile| profile.id == profile_id) {
+                profile.play_time = profile.play_time.saturating_sub(millis);
+            }
+        }
+    }
+
+    pub fn groups(&self) -> &[String] {
+        &self.group_names
+    }
+
+    /// Creates an empty group, returns false if the name is empty or already taken.
+    pub fn create_group(&mut self, group : &str) -> bool {
+        if group.is_empty() || self.group_names.iter().any(|name| name == group) {
+            return false;
+        }
+        self.group_names.push(group.to_string());
+        true
+    }
+
+    pub fn remove_group(&mut self, group : &str) {
+        self.group_names.retain(|name| name != group);
+        for entry in self.entries.iter_mut() {
+            entry.groups.retain(|name| name != group);
+        }
+    }
+
+    pub fn add_to_group(&mut self, id : AppId, group : &str) {
+        if !self.group_names.iter().any(|name| name == group) {
+            return;
+        }
+        if let Some(entry) = self.get_mut(id) && !entry.groups.iter().any(|name| name == group) {
+            entry.groups.push(group.to_string());
+        }
+    }
+
+    pub fn remove_from_group(&mut self, id : AppId, group : &str) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.groups.retain(|name| name != group);
+        }
+    }
+
+    pub fn group_entries<'a>(&'a self, group : &'a str) -> impl Iterator<Item = &'a AppEntry> + 'a {
+        self.entries.iter().filter(move |entry| entry.groups.iter().any(|name| name == group))
+    }
+
+    fn generate_id(&mut self) -> AppId {
+        self.next_id = self.next_id.max(self.entries.iter().map(|entry| entry.id + 1).max().unwrap_or(0)); //Never hand out an id that's already taken, even if next_id was lost somehow
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn sort(&mut self) { //Keeps the apps in alphabetical order, ignoring case
+        self.entries.sort_by_key(|entry| entry.name.to_lowercase());
+    }
+}
+
+const EXECUTABLE_EXTENSIONS : [&str; 6] = ["exe", "bat", "cmd", "sh", "appimage", "desktop"]; //Left off the default name of an app (see get_executable_name)
+
+pub const DEFAULT_PROFILE_NAME : &str = "Default";
+
+fn display_name(path : &str, name : &str) -> String { //Falls back to the executable name when the user leaves the name blank
+    if name.is_empty() {
+        get_executable_name(path)
+    } else {
+        name.to_string()
+    }
+}
+
+pub fn get_executable_name(path : &str) -> String{ //Gets the name of an executable from its path, to use as the default app name if the user doesn't specify one. For example, "C:\Program Files\Example\example.exe" on Windows or "/opt/example/example.sh" on Linux, will return "example"
+    let path = Path::new(path);
+    let is_executable_extension = path.extension().and_then(|extension| extension.to_str())
+        .is_some_and(|extension| EXECUTABLE_EXTENSIONS.iter().any(|executable| extension.eq_ignore_ascii_case(executable)));
+    let name = if is_executable_extension { path.file_stem() } else { path.file_name() }; //Only strip extensions that just say it's a program, "game.x86_64" and "Half-Life 2" keep theirs
+    name.map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adding_an_existing_path_renames_it() {
+        let mut library = Library::new();
+        let id = library.add("/games/game.exe", "Old Name");
+        assert_eq!(library.add("/games/game.exe", "New Name"), id);
+        assert_eq!(library.len(), 1);
+        assert_eq!(library.get(id).unwrap().name, "New Name");
+    }
+
+    #[test]
+    fn renaming_to_empty_uses_the_executable_name() {
+        let mut library = Library::new();
+        let id = library.add("/games/Hollow Knight.exe", "HK");
+        library.rename(id, "");
+        assert_eq!(library.get(id).unwrap().name, "Hollow Knight");
+        let other = library.add("/games/game.x86_64", "");
+        assert_eq!(library.get(other).unwrap().name, "game.x86_64"); //Only extensions that just say it's a program are dropped
+    }
+
+    #[test]
+    fn ids_are_never_reused() {
+        let mut library = Library::new();
+        let first = library.add("/games/a", "");
+        let second = library.add("/games/b", "");
+        library.remove(second);
+        let third = library.add("/games/c", "");
+        assert!(third != first && third != second);
+        library.next_id = 0; //Like a save where next_id was lost
+        let fourth = library.add("/games/d", "");
+        assert!(fourth > third);
+    }
+
+    #[test]
+    fn removing_a_group_removes_it_from_every_app() {
+        let mut library = Library::new();
+        let a = library.add("/games/a", "");
+        let b = library.add("/games/b", "");
+        library.create_group("Shooters");
+        library.create_group("Favourites");
+        for id in [a, b] {
+            library.add_to_group(id, "Shooters");
+            library.add_to_group(id, "Favourites");
+        }
+        library.remove_group("Shooters");
+        assert_eq!(library.groups(), ["Favourites"]);
+        assert!(library.entries().iter().all(|entry| entry.groups == ["Favourites"]));
+        assert_eq!(library.group_entries("Shooters").count(), 0);
+    }
+
+    #[test]
+    fn removing_a_profile_keeps_the_default_pointing_at_the_same_one() {
+        let mut library = Library::new();
+        let id = library.add("/games/a", "");
+        library.add_profile(id, "Vulkan");
+        library.add_profile(id, "Modded");
+        library.set_default_profile(id, 2);
+        library.remove_profile(id, 1); //Before the default, so its index moves down
+        let entry = library.get(id).unwrap();
+        assert_eq!(entry.profiles[entry.default_profile_index()].name, "Modded");
+        assert_eq!(entry.default_profile, 1);
+
+        library.remove_profile(id, 1); //The default itself
+        assert_eq!(library.get(id).unwrap().default_profile, 0);
+
+        library.remove_profile(id, 0); //The last profile can't be removed
+        assert_eq!(library.get(id).unwrap().profiles.len(), 1);
+        assert_eq!(library.get(id).unwrap().profiles[0].name, DEFAULT_PROFILE_NAME);
+    }
+
+    #[test]
+    fn play_time_follows_the_profile_id() {
+        let mut library = Library::new();
+        let id = library.add("/games/a", "");
+        library.add_profile(id, "Vulkan");
+        library.add_profile(id, "Modded");
+        let modded = library.get(id).unwrap().profiles[2].clone();
+        library.remove_profile(id, 1); //Moves Modded to index 1
+        let mut renamed = modded.clone();
+        renamed.name = "Default".to_string(); //Names don't have to be unique
+        library.set_profile(id, 1, renamed);
+        library.add_play_time(id, modded.id, 5_000);
+        library.remove_play_time(id, modded.id, 1_000);
+        let entry = library.get(id).unwrap();
+        assert_eq!(entry.play_time, 4_000);
+        assert_eq!(entry.profiles[0].play_time, 0);
+        assert_eq!(entry.profiles[1].play_time, 4_000);
+        assert_eq!(entry.profile_by_id(modded.id).unwrap().name, "Default");
+    }
+
+    #[test]
+    fn profile_ids_are_never_reused() {
+        let mut library = Library::new();
+        let id = library.add("/games/a", "");
+        let index = library.add_profile(id, "Vulkan").unwrap();
+        let vulkan = library.get(id).unwrap().profiles[index].id;
+        library.remove_profile(id, index);
+        let index = library.add_profile(id, "Vulkan").unwrap();
+        assert!(library.get(id).unwrap().profiles[index].id != vulkan);
+        library.get_mut(id).unwrap().next_profile_id = 0; //Like a save where it was lost
+        let index = library.add_profile(id, "Modded").unwrap();
+        let ids : Vec<ProfileId> = library.get(id).unwrap().profiles.iter().map(|profile| profile.id).collect();
+        assert!(!ids[..index].contains(&ids[index]));
+    }
+
+    #[test]
+    fn new_profile_names_are_unused() {
+        let mut library = Library::new();
+        let id = library.add("/games/a", "");
+        for _ in 0..2 {
+            let name = library.get(id).unwrap().unused_profile_name();
+            library.add_profile(id, &name);
+        }
+        assert_eq!(library.get(id).unwrap().profiles.iter().map(|profile| profile.name.as_str()).collect::<Vec<_>>(), [DEFAULT_PROFILE_NAME, "Profile 2", "Profile 3"]);
+        library.remove_profile(id, 1);
+        assert_eq!(library.get(id).unwrap().unused_profile_name(), "Profile 4"); //Not a second "Profile 3"
+    }
+}
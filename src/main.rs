@@ -1,5 +1,22 @@
 #![windows_subsystem = "windows"]
 mod app;
+mod artwork;
+mod dates;
+#[cfg(target_os = "linux")]
+mod desktop;
+mod history;
+mod icons;
+mod idle;
+mod launch;
+mod library;
+mod migration;
+mod notifications;
+mod search;
+mod settings;
+mod stats;
+mod steam;
+mod textures;
+mod tracker;
 fn main() -> eframe::Result {
 
     let native_options = eframe::NativeOptions {
@@ -14,7 +31,7 @@ fn main() -> eframe::Result {
         ..Default::default()
     };
     eframe::run_native(
-        "Catapult",
+        app::APP_NAME,
         native_options.clone(),
         Box::new(|cc| {
             egui_extras::install_image_loaders(&cc.egui_ctx);
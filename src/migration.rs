@@ -0,0 +1,326 @@
+use std::{collections::HashMap, fs, path::PathBuf, time::{SystemTime, UNIX_EPOCH}};
+
+use crate::history::History;
+use crate::launch::{LaunchOptions, LaunchProfile, ProfileId};
+use crate::library::{AppId, DEFAULT_PROFILE_NAME, Library};
+
+//Bump this whenever the saved shape of CatapultApp changes in a way serde(default) can't paper over, and add a migration to MIGRATIONS that upgrades from the previous version
+pub const SCHEMA_VERSION : u32 = 3;
+
+type Migration = fn(&mut Library, &mut History, &dyn eframe::Storage);
+
+//MIGRATIONS[n] upgrades a save from version n to version n + 1. Each one gets the library and history as they deserialized with the current types, plus the raw storage so it can read back any old shaped data it needs
+const MIGRATIONS : [Migration; SCHEMA_VERSION as usize] = [
+    migrate_path_keys_to_ids, //0 -> 1
+    migrate_launch_options_to_profiles, //1 -> 2
+    migrate_profile_names_to_ids, //2 -> 3
+];
+
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct SaveHeader { //Just enough of a save to find out which version wrote it
+    schema_version : u32,
+}
+
+/// Finds out which schema version the save was written with. Saves from before versioning existed count as version 0.
+pub fn saved_version(storage : &dyn eframe::Storage) -> u32 {
+    eframe::get_value::<SaveHeader>(storage, eframe::APP_KEY).unwrap_or_default().schema_version
+}
+
+/// Runs every migration between `from_version` and SCHEMA_VERSION in order.
+pub fn migrate(library : &mut Library, history : &mut History, storage : &dyn eframe::Storage, from_version : u32) {
+    for migration in MIGRATIONS.iter().skip(from_version as usize) {
+        migration(library, history, storage);
+    }
+}
+
+/// Copies the raw save into a timestamped file in the app's storage directory, so a bad migration can always be undone by hand.
+/// Returns where the copy was written, or None if there was no save to copy.
+pub fn backup_save(storage : &dyn eframe::Storage, app_name : &str, version : u32) -> std::io::Result<Option<PathBuf>> {
+    let Some(raw) = storage.get_string(eframe::APP_KEY) else {
+        return Ok(None); //Nothing saved yet, nothing to back up
+    };
+    let Some(dir) = eframe::storage_dir(app_name) else {
+        return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "no storage directory on this platform"));
+    };
+    fs::create_dir_all(&dir)?;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+    let path = dir.join(format!("app_backup_v{}_{}.ron", version, timestamp));
+    fs::write(&path, raw)?;
+    Ok(Some(path))
+}
+
+fn migrate_path_keys_to_ids(library : &mut Library, _history : &mut History, storage : &dyn eframe::Storage) { //Before the Library existed every map was keyed by the executable path
+    if !library.is_empty() {
+        return;
+    }
+    if let Some(legacy) = eframe::get_value::<LegacyState>(storage, eframe::APP_KEY) {
+        *library = legacy.into_library();
+    }
+}
+
+fn migrate_launch_options_to_profiles(library : &mut Library, _history : &mut History, storage : &dyn eframe::Storage) { //Apps used to have a single set of launch options, they become the app's default profile
+    let old_entries = eframe::get_value::<V1Save>(storage, eframe::APP_KEY).map(|save| save.library.entries).unwrap_or_default();
+    let ids : Vec<AppId> = library.entries().iter().map(|entry| entry.id).collect();
+    for id in ids {
+        let Some(entry) = library.get_mut(id) else { continue };
+        if !entry.profiles.is_empty() {
+            continue;
+        }
+        let mut profile = LaunchProfile::new(0, DEFAULT_PROFILE_NAME);
+        profile.play_time = entry.play_time; //Every session so far was played with these options
+        if let Some(old_entry) = old_entries.iter().find(|old_entry| old_entry.id == id) {
+            profile.options = old_entry.launch.clone();
+        }
+        entry.profiles.push(profile);
+        entry.default_profile = 0;
+        entry.next_profile_id = 1;
+    }
+}
+
+fn migrate_profile_names_to_ids(library : &mut Library, history : &mut History, storage : &dyn eframe::Storage) { //Sessions used to store their profile's name, which pointed at the wrong profile (or none) once profiles were renamed or shared a name
+    let ids : Vec<AppId> = library.entries().iter().map(|entry| entry.id).collect();
+    for id in ids.iter() {
+        let Some(entry) = library.get_mut(*id) else { continue };
+        for (index, profile) in entry.profiles.iter_mut().enumerate() {
+            profile.id = index as ProfileId; //The same numbers the tracker's checkpoints used for profiles, so they still add up after the upgrade
+        }
+        entry.next_profile_id = entry.profiles.len() as ProfileId;
+    }
+    let old_sessions = eframe::get_value::<V2Save>(storage, eframe::APP_KEY).map(|save| save.history.sessions).unwrap_or_default();
+    for old_session in old_sessions {
+        let Some(entry) = library.get_mut(old_session.app) else { continue };
+        let profile_id = match entry.profiles.iter().find(|profile| profile.name == old_session.profile) { //The first one with the name, there's no telling duplicates apart
+            Some(profile) => profile.id,
+            None => { //Renamed or removed since, an id no profile will ever get
+                entry.next_profile_id += 1;
+                entry.next_profile_id - 1
+            }
+        };
+        history.set_profile(old_session.id, profile_id);
+    }
+}
+
+//Version 2 saves named the profile each session was played with
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct V2Save {
+    history : V2History,
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct V2History {
+    sessions : Vec<V2Session>,
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct V2Session {
+    id : u64,
+    app : AppId,
+    profile : String,
+}
+
+//Version 1 saves kept a single LaunchOptions on each entry
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct V1Save {
+    library : V1Library,
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct V1Library {
+    entries : Vec<V1Entry>,
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct V1Entry {
+    id : AppId,
+    launch : LaunchOptions,
+}
+
+//The shape CatapultApp was saved in before the Library existed, where every map was keyed by the executable path
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct LegacyState {
+    app_folders : HashMap<String,Vec<String>>,
+    app_folder_names : Vec<String>,
+    apps : Vec<String>,
+    apps_aliases : HashMap<String, String>,
+    app_play_time : HashMap<String, u64>,
+}
+
+impl LegacyState {
+    fn into_library(self) -> Library { //Converts the old path keyed maps into a Library, giving every app a fresh id
+        let mut library = Library::new();
+        let mut ids : HashMap<String, AppId> = HashMap::new();
+
+        for path in self.apps.iter() {
+            let name = self.apps_aliases.get(path).cloned().unwrap_or_default();
+            let id = library.add(path, &name);
+            library.add_play_time(id, 0, *self.app_play_time.get(path).unwrap_or(&0));
+            ids.insert(path.clone(), id);
+        }
+
+        let mut folder_names = self.app_folder_names.clone();
+        for folder in self.app_folders.keys() { //Groups that were missing from the ordering still get kept, they just go at the end
+            if !folder_names.contains(folder) {
+                folder_names.push(folder.clone());
+            }
+        }
+        for folder in folder_names.iter() {
+            library.create_group(folder);
+            for path in self.app_folders.get(folder).into_iter().flatten() {
+                if let Some(id) = ids.get(path) { //Apps removed from "All Apps" were left behind in their groups, drop them
+                    library.add_to_group(*id, folder);
+                }
+            }
+        }
+
+        library
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MemoryStorage(HashMap<String, String>); //A save held in memory, as RON just like eframe writes it
+
+    impl eframe::Storage for MemoryStorage {
+        fn get_string(&self, key : &str) -> Option<String> {
+            self.0.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key : &str, value : String) {
+            self.0.insert(key.to_string(), value);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    #[derive(serde::Deserialize, Default)]
+    #[serde(default)]
+    struct LibrarySave { //The part of CatapultApp the migrations work on
+        library : Library,
+        history : History,
+    }
+
+    fn load(save : &str) -> (MemoryStorage, Library, History, u32) { //Loads the library and history the way CatapultApp::new does, before migrating them
+        let storage = MemoryStorage(HashMap::from([(eframe::APP_KEY.to_string(), save.to_string())]));
+        let version = saved_version(&storage);
+        let loaded = if version == 0 { LibrarySave::default() } else { eframe::get_value::<LibrarySave>(&storage, eframe::APP_KEY).unwrap() };
+        (storage, loaded.library, loaded.history, version)
+    }
+
+    #[test]
+    fn migrates_from_version_0() {
+        let (storage, mut library, mut history, version) = load(r#"(
+            apps: ["C:/games/doom.exe"],
+            apps_aliases: {"C:/games/doom.exe": "DOOM"},
+            app_play_time: {"C:/games/doom.exe": 60000},
+            app_folders: {"Shooters": ["C:/games/doom.exe"]},
+            app_folder_names: ["Shooters"],
+            selected_app: "C:/games/doom.exe",
+        )"#);
+        assert_eq!(version, 0);
+        migrate(&mut library, &mut history, &storage, version);
+        let doom = library.find_by_path("C:/games/doom.exe").unwrap();
+        assert_eq!(doom.name, "DOOM");
+        assert_eq!(doom.play_time, 60_000);
+        assert_eq!(doom.groups, ["Shooters"]);
+        assert_eq!(doom.profiles.len(), 1);
+        assert_eq!(doom.profiles[0].play_time, 60_000);
+    }
+
+    #[test]
+    fn migrates_from_version_1() {
+        let (storage, mut library, mut history, version) = load(r#"(
+            schema_version: 1,
+            library: (
+                entries: [
+                    (id: 7, path: "C:/games/doom.exe", name: "DOOM", play_time: 60000, launch: (args: ["-nomonsters"], working_dir: "C:/games")),
+                ],
+                group_names: [],
+                next_id: 8,
+            ),
+        )"#);
+        assert_eq!(version, 1);
+        migrate(&mut library, &mut history, &storage, version);
+        let doom = library.get(7).unwrap();
+        assert_eq!(doom.profiles.len(), 1);
+        assert_eq!(doom.profiles[0].name, DEFAULT_PROFILE_NAME);
+        assert_eq!(doom.profiles[0].play_time, 60_000);
+        assert_eq!(doom.profiles[0].options.args, ["-nomonsters"]);
+        assert_eq!(doom.profiles[0].options.working_dir, "C:/games");
+        assert_eq!(doom.default_profile, 0);
+        assert_eq!(doom.next_profile_id, 1);
+    }
+
+    #[test]
+    fn migrates_from_version_2() {
+        let (storage, mut library, mut history, version) = load(r#"(
+            schema_version: 2,
+            library: (
+                entries: [
+                    (id: 7, path: "C:/games/doom.exe", name: "DOOM", play_time: 60000, profiles: [
+                        (name: "Default", play_time: 10000),
+                        (name: "Modded", play_time: 50000),
+                    ]),
+                ],
+                next_id: 8,
+            ),
+            history: (
+                sessions: [
+                    (id: 0, app: 7, start: 0, end: 10000, duration: 10000, profile: "Default"),
+                    (id: 1, app: 7, start: 20000, end: 70000, duration: 50000, profile: "Modded"),
+                    (id: 2, app: 7, start: 80000, end: 80000, duration: 0, profile: "Renamed"),
+                ],
+                next_id: 3,
+            ),
+        )"#);
+        assert_eq!(version, 2);
+        migrate(&mut library, &mut history, &storage, version);
+        let doom = library.get(7).unwrap();
+        assert_eq!(doom.profiles.iter().map(|profile| profile.id).collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(history.get(0).unwrap().profile_id, 0);
+        assert_eq!(history.get(1).unwrap().profile_id, 1);
+        let renamed = history.get(2).unwrap().profile_id;
+        assert!(doom.profile_by_id(renamed).is_none());
+        let vulkan = library.add_profile(7, "Vulkan").unwrap();
+        assert!(library.get(7).unwrap().profiles[vulkan].id != renamed); //The session still doesn't belong to any profile
+    }
+
+    #[test]
+    fn legacy_state_keeps_play_time_aliases_and_groups() {
+        let legacy = LegacyState {
+            app_folders : HashMap::from([
+                ("Shooters".to_string(), vec!["C:/games/doom.exe".to_string(), "C:/games/removed.exe".to_string()]),
+                ("Favourites".to_string(), vec!["C:/games/doom.exe".to_string(), "C:/games/celeste.exe".to_string()]),
+                ("Unordered".to_string(), vec!["C:/games/celeste.exe".to_string()]),
+            ]),
+            app_folder_names : vec!["Favourites".to_string(), "Shooters".to_string()],
+            apps : vec!["C:/games/doom.exe".to_string(), "C:/games/celeste.exe".to_string()],
+            apps_aliases : HashMap::from([("C:/games/doom.exe".to_string(), "DOOM (1993)".to_string())]),
+            app_play_time : HashMap::from([("C:/games/doom.exe".to_string(), 90_000), ("C:/games/celeste.exe".to_string(), 5_000)]),
+        };
+        let library = legacy.into_library();
+
+        assert_eq!(library.len(), 2);
+        let doom = library.find_by_path("C:/games/doom.exe").unwrap();
+        assert_eq!(doom.name, "DOOM (1993)");
+        assert_eq!(doom.play_time, 90_000);
+        assert_eq!(doom.profiles[0].play_time, 90_000);
+        let celeste = library.find_by_path("C:/games/celeste.exe").unwrap();
+        assert_eq!(celeste.name, "celeste");
+        assert_eq!(celeste.play_time, 5_000);
+
+        assert_eq!(library.groups(), ["Favourites", "Shooters", "Unordered"]); //Groups missing from app_folder_names go at the end
+        assert_eq!(library.group_entries("Shooters").map(|entry| entry.id).collect::<Vec<_>>(), [doom.id]); //The removed app is dropped
+        assert_eq!(library.group_entries("Favourites").count(), 2);
+        assert_eq!(library.group_entries("Unordered").map(|entry| entry.id).collect::<Vec<_>>(), [celeste.id]);
+    }
+}
@@ -0,0 +1,54 @@
+use std::time::{Duration, Instant};
+
+use egui::{Align2, Color32, RichText};
+
+const NOTIFICATION_LIFETIME : Duration = Duration::from_secs(10); //How long a notification stays up if the user doesn't close it
+
+struct Notification {
+    message : String,
+    created : Instant,
+}
+
+//The toasts in the bottom right corner, used to tell the user about things that went wrong (there's no console to print to under windows_subsystem = "windows")
+#[derive(Default)]
+pub struct Notifications {
+    notifications : Vec<Notification>,
+}
+
+impl Notifications {
+    pub fn error(&mut self, message : impl Into<String>) {
+        self.notifications.push(Notification { message : message.into(), created : Instant::now() });
+    }
+
+    pub fn show(&mut self, ctx : &egui::Context) {
+        self.notifications.retain(|notification| notification.created.elapsed() < NOTIFICATION_LIFETIME);
+        if self.notifications.is_empty() {
+            return;
+        }
+
+        let mut notification_to_remove : Option<usize> = None;
+        egui::Area::new(egui::Id::new("Notifications"))
+        .anchor(Align2::RIGHT_BOTTOM, [-16.0, -16.0])
+        .show(ctx, |ui| {
+            for (index, notification) in self.notifications.iter().enumerate() {
+                egui::Frame::popup(ui.style())
+                .stroke(egui::Stroke::new(1.0, Color32::from_hex("#8C2F39").unwrap()))
+                .show(ui, |ui| {
+                    ui.set_max_width(480.0);
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(&notification.message).color(Color32::from_hex("#E06C75").unwrap()));
+                        if ui.button("X").clicked() {
+                            notification_to_remove = Some(index);
+                        }
+                    });
+                });
+                ui.add_space(8.0);
+            }
+        });
+
+        if let Some(index) = notification_to_remove {
+            self.notifications.remove(index);
+        }
+        ctx.request_repaint_after(Duration::from_secs(1)); //Keep repainting so the notifications disappear on time
+    }
+}
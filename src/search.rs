@@ -0,0 +1,257 @@
+use std::{cmp::Reverse, collections::HashMap, path::Path};
+
+use crate::history::History;
+use crate::library::{AppEntry, AppId, get_executable_name};
+
+//The search box, filters and sort orders for the app lists in the side panel
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum InstalledFilter {
+    #[default]
+    Any,
+    Installed, //The executable is still where the library says it is
+    Missing,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum GroupFilter {
+    #[default]
+    Any,
+    InGroup, //In at least one group
+    Ungrouped,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum PlayedFilter {
+    #[default]
+    Any,
+    Played,
+    NeverPlayed,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SortMode {
+    #[default]
+    Name,
+    LastPlayed, //Most recently played first, apps that were never played go last
+    MostPlayed,
+    DateAdded, //Oldest first
+    RecentlyAdded, //Newest first
+}
+
+impl SortMode {
+    pub const ALL : [SortMode; 5] = [SortMode::Name, SortMode::LastPlayed, SortMode::MostPlayed, SortMode::DateAdded, SortMode::RecentlyAdded];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortMode::Name => "Name",
+            SortMode::LastPlayed => "Last Played",
+            SortMode::MostPlayed => "Most Played",
+            SortMode::DateAdded => "Date Added",
+            SortMode::RecentlyAdded => "Recently Added",
+        }
+    }
+}
+
+/// Sorts the entries by `mode`. Ties, like two apps that were never played, fall back to sorting by name.
+pub fn sort_entries<'a>(entries : impl Iterator<Item = &'a AppEntry>, mode : SortMode, history : &History) -> Vec<&'a AppEntry> {
+    let mut entries : Vec<&AppEntry> = entries.collect();
+    entries.sort_by_key(|entry| entry.name.to_lowercase());
+    match mode {
+        SortMode::Name => {}
+        SortMode::LastPlayed => {
+            let mut last_played : HashMap<AppId, u64> = HashMap::new();
+            for session in history.sessions() {
+                let end = last_played.entry(session.app).or_default();
+                *end = (*end).max(session.end);
+            }
+            entries.sort_by_key(|entry| Reverse(last_played.get(&entry.id).copied().unwrap_or(0)));
+        }
+        SortMode::MostPlayed => entries.sort_by_key(|entry| Reverse(entry.play_time)),
+        SortMode::DateAdded => entries.sort_by_key(|entry| (entry.added_at, entry.id)), //Apps from before added_at was recorded are all 0, ids still go up in the order they were added
+        SortMode::RecentlyAdded => entries.sort_by_key(|entry| Reverse((entry.added_at, entry.id))),
+    }
+    entries
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AppFilter {
+    pub query : String, //Fuzzy matched against the name, executable name and tags
+    pub installed : InstalledFilter,
+    pub grouping : GroupFilter,
+    pub played : PlayedFilter,
+}
+
+impl AppFilter {
+    pub fn is_active(&self) -> bool {
+        *self != AppFilter::default()
+    }
+
+    /// The entries that pass the filters. With a search query they're ordered best match first, otherwise (and between equally good matches) they keep their order.
+    pub fn apply<'a>(&self, entries : impl Iterator<Item = &'a AppEntry>) -> Vec<&'a AppEntry> {
+        let mut matches : Vec<(i32, &AppEntry)> = entries
+            .filter(|entry| self.passes_filters(entry))
+            .filter_map(|entry| self.score(entry).map(|score| (score, entry)))
+            .collect();
+        if !self.query.trim().is_empty() {
+            matches.sort_by_key(|(score, _)| Reverse(*score)); //Stable, so equally good matches keep their order
+        }
+        matches.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    fn passes_filters(&self, entry : &AppEntry) -> bool {
+        let is_installed_ok = match self.installed {
+            InstalledFilter::Any => true,
+            InstalledFilter::Installed => Path::new(&entry.path).exists(),
+            InstalledFilter::Missing => !Path::new(&entry.path).exists(),
+        };
+        let is_grouping_ok = match self.grouping {
+            GroupFilter::Any => true,
+            GroupFilter::InGroup => !entry.groups.is_empty(),
+            GroupFilter::Ungrouped => entry.groups.is_empty(),
+        };
+        let is_played_ok = match self.played {
+            PlayedFilter::Any => true,
+            PlayedFilter::Played => entry.play_time > 0,
+            PlayedFilter::NeverPlayed => entry.play_time == 0,
+        };
+        is_installed_ok && is_grouping_ok && is_played_ok
+    }
+
+    fn score(&self, entry : &AppEntry) -> Option<i32> { //The best match across the name, executable name and tags, None if none of them match
+        let query = self.query.trim();
+        if query.is_empty() {
+            return Some(0);
+        }
+        std::iter::once(entry.name.clone())
+            .chain(std::iter::once(get_executable_name(&entry.path)))
+            .chain(entry.tags.iter().cloned())
+            .filter_map(|text| fuzzy_score(query, &text))
+            .max()
+    }
+}
+
+/// Scores how well `text` matches `query` when the query's characters appear in order but not necessarily next to each other, so "hlk" matches "Hollow Knight". Runs of consecutive characters and matches at the start of words score higher. Returns None if it doesn't match at all.
+pub fn fuzzy_score(query : &str, text : &str) -> Option<i32> {
+    let query : Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    let text : Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut query_index = 0;
+    let mut last_match : Option<usize> = None;
+    for (index, c) in text.iter().enumerate() {
+        if query_index == query.len() {
+            break;
+        }
+        if *c != query[query_index] {
+            continue;
+        }
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == index) {
+            score += 4;
+        }
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 8;
+        }
+        last_match = Some(index);
+        query_index += 1;
+    }
+    if query_index < query.len() {
+        return None;
+    }
+    Some(score - (text.len() as i32 - query.len() as i32).max(0) / 8) //Prefer shorter texts when the matches are otherwise equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::library::Library;
+
+    fn names<'a>(entries : &[&'a AppEntry]) -> Vec<&'a str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    fn query(query : &str) -> AppFilter {
+        AppFilter { query : query.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn fuzzy_matches_characters_in_order() {
+        assert!(fuzzy_score("hlk", "Hollow Knight").is_some());
+        assert!(fuzzy_score("HK", "hollow knight").is_some()); //Case doesn't matter
+        assert!(fuzzy_score("tk", "Hollow Knight").is_none()); //Out of order
+        assert!(fuzzy_score("hollow knights", "Hollow Knight").is_none());
+        assert!(fuzzy_score("hol", "Hollow Knight") > fuzzy_score("hlw", "Hollow Knight")); //Runs score higher
+        assert!(fuzzy_score("kn", "Hollow Knight") > fuzzy_score("ni", "Hollow Knight")); //So do word starts
+        assert!(fuzzy_score("doom", "DOOM") > fuzzy_score("doom", "DOOM Eternal Deluxe Edition")); //And shorter texts
+    }
+
+    #[test]
+    fn blank_queries_match_everything_in_order() {
+        let mut library = Library::new();
+        library.add("/games/b", "Celeste");
+        library.add("/games/a", "Hades");
+        let filter = query("   ");
+        assert!(filter.is_active());
+        assert_eq!(names(&filter.apply(library.entries().iter())), ["Celeste", "Hades"]);
+    }
+
+    #[test]
+    fn best_matches_come_first_and_ties_keep_their_order() {
+        let mut library = Library::new();
+        library.add("/games/1", "Dark Souls");
+        library.add("/games/2", "Doom");
+        library.add("/games/3", "Doom 3: BFG Edition");
+        let tagged = library.add("/games/4", "Quake");
+        library.set_tags(tagged, &["doom".to_string()]);
+        let entries : Vec<&AppEntry> = library.entries().iter().rev().collect(); //Quake, Doom 3: BFG Edition, Doom, Dark Souls
+        assert_eq!(names(&query("doom").apply(entries.iter().copied())), ["Quake", "Doom", "Doom 3: BFG Edition"]); //Quake's tag and Doom match equally well, so they keep their order
+        assert_eq!(names(&query("ds").apply(entries.iter().copied())), ["Dark Souls"]);
+        assert!(query("zzz").apply(entries.iter().copied()).is_empty());
+    }
+
+    #[test]
+    fn filters_by_groups_and_play_time() {
+        let mut library = Library::new();
+        let played = library.add("/games/a", "Played");
+        library.add_play_time(played, 0, 1000);
+        let grouped = library.add("/games/b", "Grouped");
+        library.create_group("Favourites");
+        library.add_to_group(grouped, "Favourites");
+        let filter = AppFilter { played : PlayedFilter::Played, ..Default::default() };
+        assert_eq!(names(&filter.apply(library.entries().iter())), ["Played"]);
+        let filter = AppFilter { played : PlayedFilter::NeverPlayed, grouping : GroupFilter::Ungrouped, ..Default::default() };
+        assert!(filter.apply(library.entries().iter()).is_empty());
+        let filter = AppFilter { installed : InstalledFilter::Missing, grouping : GroupFilter::InGroup, ..Default::default() };
+        assert_eq!(names(&filter.apply(library.entries().iter())), ["Grouped"]);
+    }
+
+    #[test]
+    fn never_played_apps_go_last() {
+        let mut library = Library::new();
+        let old = library.add("/games/a", "Old");
+        library.add("/games/b", "Zelda");
+        library.add("/games/c", "Another");
+        let recent = library.add("/games/d", "Recent");
+        let mut history = History::default();
+        for (app, start) in [(old, 1000), (recent, 5000), (old, 2000)] {
+            let id = history.start(app, 0, start);
+            history.finish(id, start + 100, None);
+        }
+        assert_eq!(names(&sort_entries(library.entries().iter(), SortMode::LastPlayed, &history)), ["Recent", "Old", "Another", "Zelda"]); //The never played ones stay in name order
+    }
+
+    #[test]
+    fn sorts_by_play_time_and_date_added() {
+        let mut library = Library::new();
+        let ids = [library.add("/games/a", "First"), library.add("/games/b", "Second"), library.add("/games/c", "Third"), library.add("/games/d", "Fourth")];
+        for (id, added_at, play_time) in [(ids[0], 0, 10), (ids[1], 0, 30), (ids[2], 2000, 30), (ids[3], 1000, 0)] {
+            library.get_mut(id).unwrap().added_at = added_at; //The first two were added before added_at was recorded
+            library.add_play_time(id, 0, play_time);
+        }
+        let history = History::default();
+        assert_eq!(names(&sort_entries(library.entries().iter(), SortMode::Name, &history)), ["First", "Fourth", "Second", "Third"]);
+        assert_eq!(names(&sort_entries(library.entries().iter(), SortMode::MostPlayed, &history)), ["Second", "Third", "First", "Fourth"]); //Ties in name order
+        assert_eq!(names(&sort_entries(library.entries().iter(), SortMode::DateAdded, &history)), ["First", "Second", "Fourth", "Third"]); //No date falls back to the id
+        assert_eq!(names(&sort_entries(library.entries().iter(), SortMode::RecentlyAdded, &history)), ["Third", "Fourth", "Second", "First"]);
+    }
+}
@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Settings { //Everything in the "Settings" window
+    pub poll_interval_secs : u64, //How often the tracker thread checks whether running apps have exited
+    pub idle_input : bool, //Stop counting play time when there's been no keyboard or mouse input for idle_minutes. Off by default, gamepad input doesn't count as input (see idle::input_idle_time)
+    pub idle_minutes : u64,
+    pub idle_cpu : bool, //Stop counting play time while the app's processes use less than idle_cpu_percent of a core, e.g. sitting in a menu or a launcher
+    pub idle_cpu_percent : f32,
+    pub artwork_folder : String, //A folder of images named after games to use as artwork for apps that don't have their own, empty for none
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs : 2,
+            idle_input : false,
+            idle_minutes : 5,
+            idle_cpu : false,
+            idle_cpu_percent : 1.0,
+            artwork_folder : "".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval_secs.max(1))
+    }
+
+    pub fn idle_input_time(&self) -> Duration {
+        Duration::from_secs(self.idle_minutes.max(1) * 60)
+    }
+}
@@ -0,0 +1,115 @@
+use crate::dates::{MILLIS_PER_DAY, civil_from_days};
+use crate::history::History;
+use crate::library::Library;
+
+//The numbers behind the Statistics screen. Totals come from the library so play time from before the history existed still counts, the charts over time can only use recorded sessions
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Period {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+pub fn total_play_time(library : &Library) -> u64 {
+    library.entries().iter().map(|entry| entry.play_time).sum()
+}
+
+/// The `count` most played apps as (name, play time), most played first. Apps that have never been played are left out.
+pub fn top_apps(library : &Library, count : usize) -> Vec<(String, u64)> {
+    let mut apps : Vec<(String, u64)> = library.entries().iter()
+        .filter(|entry| entry.play_time > 0)
+        .map(|entry| (entry.name.clone(), entry.play_time))
+        .collect();
+    apps.sort_by_key(|(_, play_time)| std::cmp::Reverse(*play_time));
+    apps.truncate(count);
+    apps
+}
+
+/// Total play time of the apps in each group, in the same order as the groups.
+pub fn group_totals(library : &Library) -> Vec<(String, u64)> {
+    library.groups().iter()
+        .map(|group| (group.clone(), library.group_entries(group).map(|entry| entry.play_time).sum()))
+        .collect()
+}
+
+/// Play time in each of the last `count` days, weeks or months up to `now`, oldest first. Each session counts towards the period it started in.
+pub fn play_time_by_period(history : &History, period : Period, count : usize, now : u64) -> Vec<(String, u64)> {
+    let current = period_index(period, now);
+    let first = current - count as i64 + 1;
+    let mut bars : Vec<(String, u64)> = (first..=current).map(|index| (period_label(period, index), 0)).collect();
+    for session in history.sessions() {
+        let index = period_index(period, session.start);
+        if index >= first && index <= current {
+            bars[(index - first) as usize].1 += session.duration;
+        }
+    }
+    bars
+}
+
+fn period_index(period : Period, millis : u64) -> i64 { //Numbers the days, weeks or months since the unix epoch
+    let days = (millis / MILLIS_PER_DAY) as i64;
+    match period {
+        Period::Day => days,
+        Period::Week => (days + 3).div_euclid(7), //The epoch was a Thursday, shift so weeks start on Monday
+        Period::Month => {
+            let (year, month, _) = civil_from_days(days);
+            year * 12 + month as i64 - 1
+        }
+    }
+}
+
+fn period_label(period : Period, index : i64) -> String {
+    match period {
+        Period::Day => {
+            let (_, month, day) = civil_from_days(index);
+            format!("{:02}-{:02}", month, day)
+        }
+        Period::Week => {
+            let (_, month, day) = civil_from_days(index * 7 - 3); //The Monday the week starts on
+            format!("{:02}-{:02}", month, day)
+        }
+        Period::Month => format!("{}-{:02}", index.div_euclid(12), index.rem_euclid(12) + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(sessions : &[(u64, u64)]) -> History { //Sessions as (start, duration)
+        let mut history = History::default();
+        for (start, duration) in sessions {
+            let id = history.start(0, 0, *start);
+            history.add_time(id, *duration, 0, start + duration);
+        }
+        history
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        assert_eq!(period_index(Period::Week, 3 * MILLIS_PER_DAY), 0); //Sunday 1970-01-04
+        assert_eq!(period_index(Period::Week, 4 * MILLIS_PER_DAY), 1); //Monday 1970-01-05
+        assert_eq!(period_index(Period::Week, 20087 * MILLIS_PER_DAY), period_index(Period::Week, 20093 * MILLIS_PER_DAY + MILLIS_PER_DAY - 1)); //2024-12-30 to the end of 2025-01-05 is one week
+        assert_eq!(period_label(Period::Week, period_index(Period::Week, 20090 * MILLIS_PER_DAY)), "12-30"); //Labelled with its Monday, in the year before
+    }
+
+    #[test]
+    fn labels_periods() {
+        assert_eq!(period_label(Period::Day, period_index(Period::Day, 19782 * MILLIS_PER_DAY)), "02-29");
+        assert_eq!(period_label(Period::Month, period_index(Period::Month, 0)), "1970-01");
+        assert_eq!(period_label(Period::Month, period_index(Period::Month, 20087 * MILLIS_PER_DAY)), "2024-12");
+    }
+
+    #[test]
+    fn buckets_sessions_by_the_period_they_started_in() {
+        let now = 20103 * MILLIS_PER_DAY + 1000; //2025-01-15
+        let first = 20102 * MILLIS_PER_DAY; //The start of the oldest of the two days
+        let bars = play_time_by_period(&history(&[(first - 1, 100), (first, 10), (first + MILLIS_PER_DAY - 1, 20), (now, 5), (now + MILLIS_PER_DAY, 1000)]), Period::Day, 2, now);
+        assert_eq!(bars, [("01-14".to_string(), 30), ("01-15".to_string(), 5)]); //The session just before the first day and the one after now are left out
+
+        let bars = play_time_by_period(&history(&[(20086 * MILLIS_PER_DAY, 7)]), Period::Month, 3, now); //A month chart that crosses the new year
+        assert_eq!(bars, [("2024-11".to_string(), 0), ("2024-12".to_string(), 7), ("2025-01".to_string(), 0)]);
+    }
+}
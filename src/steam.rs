@@ -0,0 +1,313 @@
+use std::{collections::HashSet, fs, path::{Path, PathBuf}};
+
+use crate::library::ArtworkKind;
+
+//Reads a Steam installation to find the games it has installed, so they can be imported into the library. Steam keeps everything in its VDF (KeyValues) text format:
+//steamapps/libraryfolders.vdf lists every library folder, and each library's steamapps folder has an appmanifest_<app id>.acf for every game installed in it.
+
+/// A parsed VDF value, either a string or a section of key value pairs in the order they appear.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Vdf {
+    Value(String),
+    Section(Vec<(String, Vdf)>),
+}
+
+impl Vdf {
+    /// The first value with this key in the section, ignoring case like Steam does. None if this isn't a section or doesn't have the key.
+    pub fn get(&self, key : &str) -> Option<&Vdf> {
+        match self {
+            Vdf::Section(pairs) => pairs.iter().find(|(pair_key, _)| pair_key.eq_ignore_ascii_case(key)).map(|(_, value)| value),
+            Vdf::Value(_) => None,
+        }
+    }
+
+    pub fn get_str(&self, key : &str) -> Option<&str> {
+        match self.get(key)? {
+            Vdf::Value(value) => Some(value),
+            Vdf::Section(_) => None,
+        }
+    }
+
+    /// The key value pairs of a section, empty for a plain value.
+    pub fn pairs(&self) -> &[(String, Vdf)] {
+        match self {
+            Vdf::Section(pairs) => pairs,
+            Vdf::Value(_) => &[],
+        }
+    }
+}
+
+/// Parses VDF text into the section at its root. None if the braces don't match up or a key is missing its value.
+pub fn parse_vdf(text : &str) -> Option<Vdf> {
+    let mut tokens = tokenize_vdf(text).into_iter();
+    let root = parse_section(&mut tokens, false)?;
+    Some(Vdf::Section(root))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    String(String),
+    Open,
+    Close,
+}
+
+fn tokenize_vdf(text : &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            '"' => {
+                let mut string = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => match chars.next() { //Windows paths are written with doubled backslashes
+                            Some('n') => string.push('\n'),
+                            Some('t') => string.push('\t'),
+                            Some(escaped) => string.push(escaped),
+                            None => {}
+                        },
+                        _ => string.push(c),
+                    }
+                }
+                tokens.push(Token::String(string));
+            }
+            '/' if chars.peek() == Some(&'/') => { //Comments run to the end of the line
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '[' => { //Platform conditionals like [$WIN32] after a value, we keep the value whatever the platform
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                }
+            }
+            _ if c.is_whitespace() => {}
+            _ => { //Unquoted strings end at whitespace or a brace
+                let mut string = c.to_string();
+                while let Some(c) = chars.peek() && !c.is_whitespace() && !matches!(c, '{' | '}' | '"') {
+                    string.push(*c);
+                    chars.next();
+                }
+                tokens.push(Token::String(string));
+            }
+        }
+    }
+    tokens
+}
+
+fn parse_section(tokens : &mut impl Iterator<Item = Token>, is_nested : bool) -> Option<Vec<(String, Vdf)>> { //Reads key value pairs until the closing brace, or the end of the text for the root
+    let mut pairs = Vec::new();
+    loop {
+        let key = match tokens.next() {
+            Some(Token::String(key)) => key,
+            Some(Token::Close) if is_nested => return Some(pairs),
+            None if !is_nested => return Some(pairs),
+            _ => return None,
+        };
+        let value = match tokens.next()? {
+            Token::String(value) => Vdf::Value(value),
+            Token::Open => Vdf::Section(parse_section(tokens, true)?),
+            Token::Close => return None,
+        };
+        pairs.push((key, value));
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SteamGame {
+    pub app_id : u32,
+    pub name : String,
+    pub install_dir : PathBuf, //steamapps/common/<installdir> in the library the game is installed in
+    pub artwork : Vec<(ArtworkKind, PathBuf)>, //The images from Steam's library cache, for the games Steam has shown in its library
+}
+
+/// Where Steam is installed, if it's in the usual place for this OS.
+pub fn find_steam_dir() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let candidates : Vec<PathBuf> = if cfg!(windows) {
+        ["ProgramFiles(x86)", "ProgramFiles"].iter().filter_map(std::env::var_os).map(|dir| PathBuf::from(dir).join("Steam")).collect()
+    } else if cfg!(target_os = "macos") {
+        home.iter().map(|home| home.join("Library/Application Support/Steam")).collect()
+    } else {
+        home.iter().flat_map(|home| [home.join(".steam/steam"), home.join(".local/share/Steam"), home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam")]).collect() //The last one is the Flatpak
+    };
+    candidates.into_iter().find(|dir| dir.join("steamapps").is_dir())
+}
+
+/// Every library folder Steam knows about, starting with the Steam folder itself.
+pub fn library_folders(steam_dir : &Path) -> Vec<PathBuf> {
+    let mut folders = vec![steam_dir.to_path_buf()];
+    let text = fs::read_to_string(steam_dir.join("steamapps").join("libraryfolders.vdf")).unwrap_or_default();
+    folders.extend(parse_library_folders(&text));
+    let mut seen : HashSet<PathBuf> = HashSet::new();
+    folders.retain(|folder| seen.insert(fs::canonicalize(folder).unwrap_or(folder.clone()))); //On Linux ~/.steam/steam is usually a link to the real folder, which is also listed
+    folders
+}
+
+fn parse_library_folders(text : &str) -> Vec<PathBuf> { //The paths in libraryfolders.vdf
+    let Some(root) = parse_vdf(text) else { return Vec::new() };
+    let mut folders = Vec::new();
+    for (_, libraries) in root.pairs() { //"libraryfolders", or "LibraryFolders" in older versions of Steam
+        for (key, library) in libraries.pairs() {
+            let path = match library {
+                Vdf::Section(_) => library.get_str("path"),
+                Vdf::Value(path) if key.parse::<u32>().is_ok() => Some(path.as_str()), //Older versions list the paths directly, next to keys like "TimeNextStatsReport"
+                Vdf::Value(_) => None,
+            };
+            folders.extend(path.map(PathBuf::from));
+        }
+    }
+    folders
+}
+
+/// Every game installed in the Steam at `steam_dir`, sorted by name. Tools like Proton and the Steamworks redistributables are left out.
+pub fn scan_steam_games(steam_dir : &Path) -> Vec<SteamGame> {
+    let mut games : Vec<SteamGame> = Vec::new();
+    for library in library_folders(steam_dir) {
+        let steamapps = library.join("steamapps");
+        for file in fs::read_dir(&steamapps).into_iter().flatten().flatten() {
+            let file_name = file.file_name().to_string_lossy().to_string();
+            if !(file_name.starts_with("appmanifest_") && file_name.ends_with(".acf")) {
+                continue;
+            }
+            if let Ok(text) = fs::read_to_string(file.path()) && let Some(game) = parse_app_manifest(&text, &steamapps, steam_dir) && !games.iter().any(|other| other.app_id == game.app_id) {
+                games.push(game);
+            }
+        }
+    }
+    games.sort_by_key(|game| game.name.to_lowercase());
+    games
+}
+
+fn parse_app_manifest(text : &str, steamapps : &Path, steam_dir : &Path) -> Option<SteamGame> {
+    let root = parse_vdf(text)?;
+    let state = root.get("AppState")?;
+    let app_id = state.get_str("appid")?.parse().ok()?;
+    let name = state.get_str("name")?.to_string();
+    let install_dir = steamapps.join("common").join(state.get_str("installdir")?);
+    let is_installed = state.get_str("StateFlags").and_then(|flags| flags.parse::<u32>().ok()).is_none_or(|flags| flags & 4 != 0); //4 is "fully installed", games that are still downloading don't have it
+    let is_tool = name.starts_with("Proton") || name.starts_with("Steam Linux Runtime") || name.starts_with("Steamworks");
+    if !is_installed || is_tool {
+        return None;
+    }
+    Some(SteamGame { app_id, name, install_dir, artwork : cached_artwork(steam_dir, app_id) })
+}
+
+fn cached_artwork(steam_dir : &Path, app_id : u32) -> Vec<(ArtworkKind, PathBuf)> { //Newer versions of Steam keep each game's images in a folder named after its app id, older ones prefix the file names with it
+    let cache = steam_dir.join("appcache").join("librarycache");
+    let kinds = [
+        (ArtworkKind::Cover, "library_600x900.jpg"),
+        (ArtworkKind::Background, "library_hero.jpg"),
+        (ArtworkKind::Logo, "logo.png"),
+        (ArtworkKind::Icon, "icon.jpg"),
+    ];
+    kinds.iter()
+        .filter_map(|(kind, file_name)| {
+            [cache.join(app_id.to_string()).join(file_name), cache.join(format!("{}_{}", app_id, file_name))].into_iter()
+                .find(|path| path.is_file())
+                .map(|path| (*kind, path))
+        })
+        .collect()
+}
+
+/// The URL that has Steam start the game, the same one its desktop shortcuts use.
+pub fn run_game_url(app_id : u32) -> String {
+    format!("steam://rungameid/{}", app_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_sections() {
+        let root = parse_vdf("\"AppState\"\n{\n\t\"appid\"\t\t\"570\"\n\t\"UserConfig\"\n\t{\n\t\t\"language\"\t\t\"english\"\n\t}\n}\n").unwrap();
+        let state = root.get("appstate").unwrap(); //Keys ignore case
+        assert_eq!(state.get_str("AppID"), Some("570"));
+        assert_eq!(state.get("UserConfig").and_then(|config| config.get_str("language")), Some("english"));
+        assert_eq!(state.get_str("UserConfig"), None);
+    }
+
+    #[test]
+    fn parses_escapes_comments_and_conditionals() {
+        let root = parse_vdf(r#"
+            // A comment
+            "Root"
+            {
+                "path"      "C:\\Program Files (x86)\\Steam" // After a value
+                "quote"     "say \"hi\""
+                "windows"   "1" [$WIN32]
+                "unquoted"  value
+                "empty"     ""
+            }
+        "#).unwrap();
+        let section = root.get("Root").unwrap();
+        assert_eq!(section.get_str("path"), Some(r"C:\Program Files (x86)\Steam"));
+        assert_eq!(section.get_str("quote"), Some("say \"hi\""));
+        assert_eq!(section.get_str("windows"), Some("1"));
+        assert_eq!(section.get_str("unquoted"), Some("value"));
+        assert_eq!(section.get_str("empty"), Some(""));
+        assert_eq!(section.pairs().len(), 5);
+    }
+
+    #[test]
+    fn rejects_unbalanced_braces() {
+        assert!(parse_vdf(r#""Root" { "key" "value""#).is_none());
+        assert!(parse_vdf(r#""Root" { "key" }"#).is_none());
+        assert!(parse_vdf(r#""key" "value" }"#).is_none());
+    }
+
+    #[test]
+    fn reads_library_folders_in_both_formats() {
+        let new_format = r#"
+            "libraryfolders"
+            {
+                "0"
+                {
+                    "path"      "c:\\program files (x86)\\steam"
+                    "label"     ""
+                    "apps"
+                    {
+                        "228980"    "170358836"
+                    }
+                }
+                "1"
+                {
+                    "path"      "D:\\SteamLibrary"
+                }
+            }
+        "#;
+        assert_eq!(parse_library_folders(new_format), [PathBuf::from(r"c:\program files (x86)\steam"), PathBuf::from(r"D:\SteamLibrary")]);
+        let old_format = r#"
+            "LibraryFolders"
+            {
+                "TimeNextStatsReport"   "1700000000"
+                "ContentStatsID"        "-123"
+                "1"     "/mnt/games/SteamLibrary"
+            }
+        "#;
+        assert_eq!(parse_library_folders(old_format), [PathBuf::from("/mnt/games/SteamLibrary")]);
+        assert!(parse_library_folders("not vdf {").is_empty());
+    }
+
+    #[test]
+    fn reads_installed_games_from_manifests() {
+        let steamapps = Path::new("/steam/steamapps");
+        let manifest = |name : &str, flags : &str| format!("\"AppState\" {{ \"appid\" \"570\" \"name\" \"{}\" \"StateFlags\" \"{}\" \"installdir\" \"dota 2 beta\" }}", name, flags);
+        let game = parse_app_manifest(&manifest("Dota 2", "4"), steamapps, Path::new("/steam")).unwrap();
+        assert_eq!(game.app_id, 570);
+        assert_eq!(game.name, "Dota 2");
+        assert_eq!(game.install_dir, steamapps.join("common").join("dota 2 beta"));
+        assert!(parse_app_manifest(&manifest("Dota 2", "1026"), steamapps, Path::new("/steam")).is_none()); //Still downloading
+        assert!(parse_app_manifest(&manifest("Proton 9.0", "4"), steamapps, Path::new("/steam")).is_none());
+        assert!(parse_app_manifest(&manifest("Steamworks Common Redistributables", "4"), steamapps, Path::new("/steam")).is_none());
+        assert!(parse_app_manifest(r#""AppState" { "name" "No Id" }"#, steamapps, Path::new("/steam")).is_none());
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use egui::TextureHandle;
+
+//Every icon and image texture Catapult has uploaded, so nothing is uploaded twice. Each image can be cached at a few sizes, and the ones that haven't been drawn for a while are dropped once they take up too much GPU memory.
+
+const MAX_BYTES : usize = 256 * 1024 * 1024; //Roughly what the cached textures may use before the least recently drawn ones are dropped
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TextureTier {
+    Small, //The app lists
+    Medium, //Windows and thumbnails
+    Large, //The grid and the app's page
+    Full, //Backgrounds that fill the panel
+}
+
+impl TextureTier {
+    /// The most pixels an image at this tier needs along its longest side.
+    pub fn pixels(&self) -> u32 {
+        match self {
+            TextureTier::Small => 48,
+            TextureTier::Medium => 128,
+            TextureTier::Large => 512,
+            TextureTier::Full => 2048,
+        }
+    }
+}
+
+struct CachedTexture {
+    handle : TextureHandle,
+    bytes : usize,
+    last_used : u64, //The frame it was last drawn in
+}
+
+pub struct TextureCache {
+    textures : HashMap<(String, TextureTier), CachedTexture>, //Keyed by what the image is of (an executable path, or an artwork file) and its tier
+    frame : u64,
+    bytes : usize,
+    max_bytes : usize, //Always MAX_BYTES outside of the tests
+}
+
+impl Default for TextureCache {
+    fn default() -> Self {
+        Self {
+            textures : HashMap::new(),
+            frame : 0,
+            bytes : 0,
+            max_bytes : MAX_BYTES,
+        }
+    }
+}
+
+impl TextureCache {
+    /// The texture for `key` at `tier`, marking it as used this frame.
+    pub fn get(&mut self, key : &str, tier : TextureTier) -> Option<TextureHandle> {
+        let texture = self.textures.get_mut(&(key.to_string(), tier))?;
+        texture.last_used = self.frame;
+        Some(texture.handle.clone())
+    }
+
+    /// The texture for `key` at whichever tier is cached closest to `tier`, for showing something while the right size loads.
+    pub fn get_nearest(&mut self, key : &str, tier : TextureTier) -> Option<TextureHandle> {
+        let frame = self.frame;
+        let texture = self.textures.iter_mut()
+            .filter(|((cached_key, _), _)| cached_key == key)
+            .min_by_key(|((_, cached_tier), _)| cached_tier.pixels().abs_diff(tier.pixels()))
+            .map(|(_, texture)| texture)?;
+        texture.last_used = frame;
+        Some(texture.handle.clone())
+    }
+
+    pub fn insert(&mut self, key : String, tier : TextureTier, handle : TextureHandle) {
+        let [width, height] = handle.size();
+        let bytes = width * height * 4;
+        self.bytes += bytes;
+        if let Some(old) = self.textures.insert((key, tier), CachedTexture { handle, bytes, last_used : self.frame }) {
+            self.bytes -= old.bytes;
+        }
+    }
+
+    /// Drops every tier of `key`, for images that have been replaced.
+    pub fn remove(&mut self, key : &str) {
+        let mut freed = 0;
+        self.textures.retain(|(cached_key, _), texture| {
+            let is_kept = cached_key != key;
+            if !is_kept {
+                freed += texture.bytes;
+            }
+            is_kept
+        });
+        self.bytes -= freed;
+    }
+
+    /// Called once at the end of every frame. Drops the least recently drawn textures until they fit in the budget again, never ones drawn this frame.
+    pub fn end_frame(&mut self) {
+        if self.bytes > self.max_bytes {
+            let mut by_age : Vec<((String, TextureTier), u64, usize)> = self.textures.iter()
+                .filter(|(_, texture)| texture.last_used < self.frame)
+                .map(|(key, texture)| (key.clone(), texture.last_used, texture.bytes))
+                .collect();
+            by_age.sort_by_key(|(_, last_used, _)| *last_used);
+            for (key, _, bytes) in by_age {
+                if self.bytes <= self.max_bytes {
+                    break;
+                }
+                self.textures.remove(&key); //Dropping the last handle frees the GPU texture
+                self.bytes -= bytes;
+            }
+        }
+        self.frame += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texture(ctx : &egui::Context, size : usize) -> TextureHandle { //size * size * 4 bytes
+        ctx.load_texture("test", egui::ColorImage::filled([size, size], egui::Color32::WHITE), egui::TextureOptions::LINEAR)
+    }
+
+    #[test]
+    fn drops_the_least_recently_drawn_textures_over_the_budget() {
+        let ctx = egui::Context::default();
+        let mut cache = TextureCache { max_bytes : 150, ..Default::default() };
+        for key in ["a", "b", "c"] { //64 bytes each, one per frame
+            cache.insert(key.to_string(), TextureTier::Small, texture(&ctx, 4));
+            cache.end_frame();
+        }
+        assert!(cache.get("a", TextureTier::Small).is_none()); //Dropped once c went over the budget
+        assert!(cache.get("b", TextureTier::Small).is_some());
+        cache.insert("d".to_string(), TextureTier::Small, texture(&ctx, 4));
+        cache.end_frame();
+        assert!(cache.get("b", TextureTier::Small).is_some()); //Drawn in that frame, so c went instead
+        assert!(cache.get("c", TextureTier::Small).is_none());
+        assert!(cache.get("d", TextureTier::Small).is_some());
+        assert_eq!(cache.bytes, 128);
+    }
+
+    #[test]
+    fn never_drops_textures_drawn_this_frame() {
+        let ctx = egui::Context::default();
+        let mut cache = TextureCache { max_bytes : 0, ..Default::default() };
+        cache.insert("a".to_string(), TextureTier::Small, texture(&ctx, 4));
+        cache.insert("b".to_string(), TextureTier::Large, texture(&ctx, 8));
+        cache.end_frame();
+        assert!(cache.get("a", TextureTier::Small).is_some());
+        assert!(cache.get_nearest("b", TextureTier::Small).is_some());
+        cache.end_frame();
+        assert_eq!(cache.textures.len(), 2);
+        cache.end_frame(); //Neither was drawn
+        assert!(cache.textures.is_empty());
+        assert_eq!(cache.bytes, 0);
+    }
+
+    #[test]
+    fn picks_the_nearest_tier() {
+        let ctx = egui::Context::default();
+        let mut cache = TextureCache::default();
+        let small = texture(&ctx, 1);
+        let large = texture(&ctx, 2);
+        cache.insert("a".to_string(), TextureTier::Small, small.clone());
+        cache.insert("a".to_string(), TextureTier::Large, large.clone());
+        cache.insert("b".to_string(), TextureTier::Full, texture(&ctx, 3));
+        assert_eq!(cache.get_nearest("a", TextureTier::Medium).map(|handle| handle.id()), Some(small.id()));
+        assert_eq!(cache.get_nearest("a", TextureTier::Full).map(|handle| handle.id()), Some(large.id()));
+        assert!(cache.get_nearest("c", TextureTier::Small).is_none());
+        assert!(cache.get("a", TextureTier::Full).is_none()); //Only get_nearest falls back to other tiers
+    }
+
+    #[test]
+    fn removing_a_key_drops_every_tier() {
+        let ctx = egui::Context::default();
+        let mut cache = TextureCache::default();
+        cache.insert("a".to_string(), TextureTier::Small, texture(&ctx, 1));
+        cache.insert("a".to_string(), TextureTier::Small, texture(&ctx, 2)); //Replacing a texture only counts the new one
+        cache.insert("a".to_string(), TextureTier::Large, texture(&ctx, 4));
+        cache.insert("b".to_string(), TextureTier::Small, texture(&ctx, 1));
+        assert_eq!(cache.bytes, 16 + 64 + 4);
+        cache.remove("a");
+        assert_eq!(cache.bytes, 4);
+        assert!(cache.get_nearest("a", TextureTier::Small).is_none());
+    }
+}
@@ -0,0 +1,503 @@
+use std::{collections::HashSet, fs, path::{Path, PathBuf}, process::Child, sync::mpsc::{self, Receiver, Sender, TryIter}, thread::{self, JoinHandle}, time::{Duration, Instant}};
+
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use crate::dates::now_millis;
+use crate::idle::input_idle_time;
+use crate::settings::Settings;
+use crate::launch::ProfileId;
+use crate::library::{AppEntry, AppId, Library};
+
+const TREE_SCAN_INTERVAL : Duration = Duration::from_secs(5); //How often the full process list is read to find new children of tracked apps. In between only the tracked PIDs are checked
+const CHECKPOINT_INTERVAL : u64 = 60 * 1000; //How often (in milliseconds) play time is handed to the UI and written to the checkpoint file, so a crash loses at most this much
+const ATTACH_SCAN_INTERVAL : Duration = Duration::from_secs(10); //How often to look for library apps that were started outside of Catapult
+const LAUNCHERS : [&str; 23] = [ //Programs that start other apps, like the wrappers in .desktop files. A running one doesn't mean any particular app in the library is running
+    "steam", "flatpak", "snap", "env", "sh", "bash", "zsh", "cmd", "powershell", "pwsh", "explorer", "xdg-open",
+    "python", "java", "javaw", "node", "perl", "ruby", "mono", "dotnet", "wine", "lutris", "gamemoderun",
+];
+const LAUNCH_TIMEOUT : u64 = 5 * 60 * 1000; //How long (in milliseconds) a session without a process waits for the game to show up, Steam may have an update to download first
+
+//A running app that's being tracked for play time. Games started through a launcher or bootstrapper often exit straight away and leave the real game running as a child, so the whole process tree is followed instead of just the PID we spawned
+pub struct Session {
+    pub app : AppId,
+    pub profile : ProfileId, //The launch profile it was started with, so play time can be broken down per profile
+    known_pids : HashSet<(Pid, u64)>, //Every process seen in the tree so far with its start time, since PIDs get reused. Dead ones are kept so children that outlive their parent can still be matched to it
+    process_name : String, //If set, any process with this name keeps the session alive even if it isn't in the tree
+    tracked_dir : Option<PathBuf>, //If set, any process running from inside this folder keeps the session alive even if it isn't in the tree (see is_in_dir)
+    launch_deadline : u64, //For sessions started without a process, the Unix timestamp in milliseconds until which the session waits for one to show up
+    pub record : Option<u64>, //Id of the SessionRecord in the History for this session, None until the UI has opened one for a session the tracker found on its own
+    pub last_checkpoint : u64, //Unix timestamp in milliseconds up to which play time has been handed to the UI, starts at the session's start
+    last_poll : u64, //Unix timestamp in milliseconds of the last time the session was checked, for working out idle time
+    idle_since_checkpoint : u64, //Milliseconds since last_checkpoint that the user was idle, these don't count towards play time
+    is_cpu_sampled : bool, //CPU usage needs two refreshes to mean anything, so the first poll can't count as idle
+    child : Option<Child>, //The process we spawned, None for apps we attached to. Kept so it can be waited on for its exit code (which also stops it lingering as a zombie on unix)
+    exit_code : Option<i32>,
+}
+
+impl Session {
+    pub fn new(app : AppId, profile : ProfileId, pid : usize, process_name : &str) -> Self {
+        Self {
+            app,
+            profile,
+            known_pids : HashSet::from([(Pid::from(pid), 0)]), //The start time is filled in by resolve_start_times once the tracker has the process list
+            process_name : normalize_process_name(process_name),
+            tracked_dir : None,
+            launch_deadline : 0,
+            record : None,
+            last_checkpoint : now_millis(),
+            last_poll : now_millis(),
+            idle_since_checkpoint : 0,
+            is_cpu_sampled : false,
+            child : None,
+            exit_code : None,
+        }
+    }
+
+    /// A session for an app started through another program, like a steam:// URL, so there's no process to follow yet. It waits for one matching the process name or tracked folder to show up, and the time spent waiting counts as idle. If none has by LAUNCH_TIMEOUT the session ends with NeverStarted instead.
+    pub fn waiting(app : AppId, profile : ProfileId, process_name : &str) -> Self {
+        let mut session = Self::new(app, profile, 0, process_name);
+        session.known_pids.clear();
+        session.launch_deadline = now_millis() + LAUNCH_TIMEOUT;
+        session
+    }
+
+    pub fn with_tracked_dir(mut self, dir : &str) -> Self {
+        self.tracked_dir = (!dir.is_empty()).then(|| PathBuf::from(dir));
+        self
+    }
+
+    pub fn with_child(mut self, child : Child) -> Self {
+        self.child = Some(child);
+        self
+    }
+
+    /// The exit code of the process we spawned, once it has exited.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    fn wait_for_child(&mut self) { //Picks up the exit code of the process we spawned once it's done
+        if let Some(child) = self.child.as_mut() && let Ok(Some(status)) = child.try_wait() {
+            self.exit_code = status.code();
+            self.child = None;
+        }
+    }
+
+    fn resolve_start_times(&mut self, sys : &System) { //Fills in the start time of the process the session was created with. If it has already exited it keeps 0, which no running process matches
+        self.known_pids = self.known_pids.iter()
+            .map(|(pid, start_time)| match sys.process(*pid) {
+                Some(process) if *start_time == 0 => (*pid, process.start_time()),
+                _ => (*pid, *start_time),
+            })
+            .collect();
+    }
+
+    fn is_known(&self, pid : Pid, sys : &System) -> bool { //Whether the process with this PID right now is one we've seen, not an unrelated one that was given a recycled PID
+        sys.process(pid).is_some_and(|process| self.known_pids.contains(&(pid, process.start_time())))
+    }
+
+    fn is_known_parent(&self, process : &sysinfo::Process, sys : &System) -> bool { //Whether the process's parent is in the tree. A parent that has exited still counts, as long as the process started after it did
+        let Some(parent) = process.parent() else { return false };
+        match sys.process(parent) {
+            Some(_) => self.is_known(parent, sys),
+            None => self.known_pids.iter().any(|(pid, start_time)| *pid == parent && *start_time <= process.start_time()),
+        }
+    }
+
+    fn follow_tree(&mut self, sys : &System) { //Adds any new children (or processes matching the tracked process name) to the tree. `sys` must have the full process list refreshed
+        loop { //Keep going until nothing new is found, so grandchildren get picked up in the same pass
+            let children : Vec<(Pid, u64)> = sys.processes().iter()
+                .filter(|(pid, process)| !self.is_known(**pid, sys) && (self.is_known_parent(process, sys) || self.matches_name(process) || self.matches_dir(process)))
+                .map(|(pid, process)| (*pid, process.start_time()))
+                .collect();
+            if children.is_empty() {
+                break;
+            }
+            self.known_pids.extend(children);
+        }
+    }
+
+    fn is_alive(&self, sys : &System, now : u64) -> bool {
+        self.known_pids.iter().any(|(pid, _)| self.is_known(*pid, sys)) || self.is_waiting(now)
+    }
+
+    fn is_waiting(&self, now : u64) -> bool { //Still waiting for the first process of a session started without one
+        !self.has_started() && now < self.launch_deadline
+    }
+
+    fn has_started(&self) -> bool { //Whether a process has ever been found for the session. PIDs are never dropped from the tree, so this stays true after they exit
+        !self.known_pids.is_empty()
+    }
+
+    fn cpu_usage(&self, sys : &System) -> f32 { //Across the whole tree, as a percentage of one core
+        self.known_pids.iter().filter(|(pid, _)| self.is_known(*pid, sys)).filter_map(|(pid, _)| sys.process(*pid)).map(|process| process.cpu_usage()).sum()
+    }
+
+    fn checkpoint(&mut self, now : u64) -> (u64, u64) { //Returns the active and idle milliseconds since the last checkpoint and starts counting again from now
+        let elapsed = now.saturating_sub(self.last_checkpoint);
+        let idle = self.idle_since_checkpoint.min(elapsed);
+        self.last_checkpoint = now;
+        self.idle_since_checkpoint = 0;
+        (elapsed - idle, idle)
+    }
+
+    fn matches_name(&self, process : &sysinfo::Process) -> bool {
+        !self.process_name.is_empty() && normalize_process_name(&process.name().to_string_lossy()) == self.process_name
+    }
+
+    fn matches_dir(&self, process : &sysinfo::Process) -> bool { //Needs the processes to have been refreshed with dir_refresh_kind
+        self.tracked_dir.as_ref().is_some_and(|dir| is_in_dir(process, dir))
+    }
+}
+
+fn normalize_process_name(name : &str) -> String { //So "Game.exe", "game.exe" and "game" all match
+    let name = name.trim().to_lowercase();
+    name.strip_suffix(".exe").map(str::to_string).unwrap_or(name)
+}
+
+pub enum TrackerEvent {
+    Started { app : AppId, profile : ProfileId, record : Option<u64>, start : u64 }, //The tracker is watching a session's process tree. The record is None for apps it found running on its own, the UI answers those with set_record
+    Played { app : AppId, profile : ProfileId, record : u64, millis : u64, idle : u64, now : u64 }, //Wall clock time since the last checkpoint, split into the time played and the time the user was idle
+    Stopped { app : AppId, record : u64, exit_code : Option<i32>, now : u64 }, //Always comes after a final Played for the session
+    NeverStarted { app : AppId, record : u64 }, //A waiting session timed out before any process showed up, e.g. the Steam launch failed or was cancelled. It never sent Played, so its record can be dropped
+}
+
+enum TrackerCommand {
+    Track(Session),
+    Untrack(AppId),
+    SetRecord(AppId, u64),
+    SetLibrary(Library),
+    SetSettings(Settings),
+    Shutdown,
+}
+
+//The handle to the tracker thread. Play time is worked out from timestamps on its own thread, so it doesn't matter whether the UI is repainting, minimized or stalled
+pub struct Tracker {
+    commands : Sender<TrackerCommand>,
+    events : Receiver<TrackerEvent>,
+    thread : Option<JoinHandle<()>>,
+}
+
+impl Tracker {
+    /// Starts the tracker thread, which polls and detects idling as the settings say. It asks `ctx` to repaint whenever it has events for the UI, and writes running sessions to `checkpoint_path` so they can be recovered after a crash (see read_checkpoints).
+    pub fn spawn(ctx : egui::Context, settings : Settings, checkpoint_path : Option<PathBuf>) -> Self {
+        let (command_sender, command_receiver) = mpsc::channel();
+        let (event_sender, event_receiver) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("Tracker".to_string())
+            .spawn(move || run_tracker(ctx, settings, command_receiver, event_sender, checkpoint_path))
+            .ok();
+        Self {
+            commands : command_sender,
+            events : event_receiver,
+            thread,
+        }
+    }
+
+    pub fn track(&self, session : Session) {
+        let _ = self.commands.send(TrackerCommand::Track(session));
+    }
+
+    /// Stops tracking the app without sending any more events for it, for when the app or its running session has been deleted.
+    pub fn untrack(&self, app : AppId) {
+        let _ = self.commands.send(TrackerCommand::Untrack(app));
+    }
+
+    /// Gives a session the tracker found on its own the history record the UI opened for it. Its play time is held back until then.
+    pub fn set_record(&self, app : AppId, record : u64) {
+        let _ = self.commands.send(TrackerCommand::SetRecord(app, record));
+    }
+
+    /// The apps to look for among the running processes, sent again whenever the library changes.
+    pub fn set_library(&self, library : Library) {
+        let _ = self.commands.send(TrackerCommand::SetLibrary(library));
+    }
+
+    pub fn set_settings(&self, settings : Settings) {
+        let _ = self.commands.send(TrackerCommand::SetSettings(settings));
+    }
+
+    /// Everything that has happened since the last call.
+    pub fn events(&self) -> TryIter<'_, TrackerEvent> {
+        self.events.try_iter()
+    }
+
+    /// Writes a final checkpoint and waits for the thread to finish.
+    pub fn shutdown(&mut self) {
+        let _ = self.commands.send(TrackerCommand::Shutdown);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run_tracker(ctx : egui::Context, mut settings : Settings, commands : Receiver<TrackerCommand>, events : Sender<TrackerEvent>, checkpoint_path : Option<PathBuf>) {
+    let mut sys = System::new();
+    let mut sessions : Vec<Session> = Vec::new();
+    let mut last_tree_scan = Instant::now();
+    let mut library = Library::new();
+    let mut last_attach_scan : Option<Instant> = None; //None until the UI has sent the library, so the first scan picks up games that were still running when Catapult was closed
+    loop {
+        let mut received : Vec<TrackerCommand> = Vec::new();
+        match commands.recv_timeout(settings.poll_interval()) { //Sleeps until the next poll, unless the UI has something for us
+            Ok(command) => received.push(command),
+            Err(mpsc::RecvTimeoutError::Disconnected) => received.push(TrackerCommand::Shutdown),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+        }
+        received.extend(commands.try_iter());
+
+        let now = now_millis();
+        let mut has_events = false;
+        let mut has_changed = false;
+        for command in received {
+            match command {
+                TrackerCommand::Track(mut session) => {
+                    sys.refresh_processes_specifics(ProcessesToUpdate::All, true, tree_refresh_kind(std::slice::from_ref(&session)));
+                    session.resolve_start_times(&sys);
+                    session.follow_tree(&sys); //Straight away, in case it's a launcher stub that's about to exit
+                    let _ = events.send(TrackerEvent::Started { app : session.app, profile : session.profile, record : session.record, start : session.last_checkpoint });
+                    sessions.retain(|other| other.app != session.app || other.record.is_some()); //The UI launched an app we had just found running, its session wins
+                    sessions.push(session);
+                    has_events = true;
+                    has_changed = true;
+                }
+                TrackerCommand::Untrack(app) => {
+                    sessions.retain(|session| session.app != app);
+                    has_changed = true; //So it's dropped from the checkpoint file too
+                }
+                TrackerCommand::SetRecord(app, record) => {
+                    if let Some(session) = sessions.iter_mut().find(|session| session.app == app && session.record.is_none()) {
+                        session.record = Some(record);
+                        has_changed = true;
+                    }
+                }
+                TrackerCommand::SetLibrary(new_library) => {
+                    library = new_library;
+                    last_attach_scan = last_attach_scan.or(Some(Instant::now() - ATTACH_SCAN_INTERVAL)); //Scan straight away the first time
+                }
+                TrackerCommand::SetSettings(new_settings) => settings = new_settings,
+                TrackerCommand::Shutdown => {
+                    write_checkpoints(checkpoint_path.as_deref(), &sessions, now); //The UI is about to exit so it won't see the time since the last checkpoint, the file makes sure it gets counted on the next start
+                    return;
+                }
+            }
+        }
+
+        if last_attach_scan.is_some_and(|last_scan| last_scan.elapsed() >= ATTACH_SCAN_INTERVAL) {
+            let refresh_kind = if library.entries().iter().any(|entry| !entry.tracked_dir.is_empty()) { dir_refresh_kind() } else { ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet) }; //Usually only the executable paths are needed to match against the library
+            sys.refresh_processes_specifics(ProcessesToUpdate::All, true, refresh_kind);
+            for mut session in find_untracked_apps(&sys, &library, |app| sessions.iter().any(|session| session.app == app)) {
+                session.resolve_start_times(&sys);
+                session.follow_tree(&sys);
+                let _ = events.send(TrackerEvent::Started { app : session.app, profile : session.profile, record : None, start : session.last_checkpoint });
+                sessions.push(session);
+                has_events = true;
+            }
+            last_attach_scan = Some(Instant::now());
+        }
+
+        if !sessions.is_empty() {
+            for session in sessions.iter_mut() {
+                session.wait_for_child();
+            }
+            let tracked_pids : Vec<Pid> = sessions.iter().flat_map(|session| session.known_pids.iter().map(|(pid, _)| *pid)).collect();
+            let refresh_kind = if settings.idle_cpu { ProcessRefreshKind::nothing().with_cpu() } else { ProcessRefreshKind::nothing() };
+            sys.refresh_processes_specifics(ProcessesToUpdate::Some(&tracked_pids), true, refresh_kind);
+            if last_tree_scan.elapsed() >= TREE_SCAN_INTERVAL || sessions.iter().any(|session| !session.is_alive(&sys, now)) { //Look for new children before deciding a session has stopped
+                sys.refresh_processes_specifics(ProcessesToUpdate::All, true, tree_refresh_kind(&sessions));
+                for session in sessions.iter_mut() {
+                    session.follow_tree(&sys);
+                }
+                last_tree_scan = Instant::now();
+            }
+        }
+
+        let is_input_idle = settings.idle_input && input_idle_time().is_some_and(|idle_time| idle_time >= settings.idle_input_time());
+        sessions.retain_mut(|session| {
+            let is_running = session.is_alive(&sys, now);
+            let is_cpu_idle = settings.idle_cpu && session.is_cpu_sampled && session.cpu_usage(&sys) < settings.idle_cpu_percent;
+            if is_input_idle || is_cpu_idle || session.is_waiting(now) { //The whole time since the last poll counts as idle
+                session.idle_since_checkpoint += now.saturating_sub(session.last_poll);
+            }
+            session.last_poll = now;
+            session.is_cpu_sampled = true;
+            let Some(record) = session.record else {
+                return true; //Play time keeps adding up from last_checkpoint until the UI has a record to put it in
+            };
+            if !session.has_started() { //The wait is held back until the game shows up, so a launch that never does leaves nothing in the history
+                if !is_running {
+                    let _ = events.send(TrackerEvent::NeverStarted { app : session.app, record });
+                    has_events = true;
+                    has_changed = true;
+                }
+                return is_running;
+            }
+            if !is_running || now.saturating_sub(session.last_checkpoint) >= CHECKPOINT_INTERVAL {
+                let (millis, idle) = session.checkpoint(now);
+                let _ = events.send(TrackerEvent::Played { app : session.app, profile : session.profile, record, millis, idle, now });
+                has_events = true;
+                has_changed = true;
+            }
+            if !is_running {
+                let _ = events.send(TrackerEvent::Stopped { app : session.app, record, exit_code : session.exit_code(), now });
+            }
+            is_running
+        });
+        if has_changed {
+            write_checkpoints(checkpoint_path.as_deref(), &sessions, now);
+        }
+        if has_events {
+            ctx.request_repaint(); //The UI only repaints when something happens, so wake it up to handle the events
+        }
+    }
+}
+
+fn tree_refresh_kind(sessions : &[Session]) -> ProcessRefreshKind { //Executable paths are only read when a session has a tracked folder to match them against
+    if sessions.iter().any(|session| session.tracked_dir.is_some()) { dir_refresh_kind() } else { ProcessRefreshKind::nothing() }
+}
+
+fn dir_refresh_kind() -> ProcessRefreshKind { //What is_in_dir needs to know about each process
+    ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet).with_cwd(UpdateKind::OnlyIfNotSet).with_cmd(UpdateKind::OnlyIfNotSet)
+}
+
+/// Whether the process is running from inside `dir`: its executable is in there, or it was started in there or with a file from in there. Proton games run Wine's executable from Proton's folder, so only the last two catch them. Shells and other launchers only count by their executable, or a terminal open in a game's folder would count as playing it.
+fn is_in_dir(process : &sysinfo::Process, dir : &Path) -> bool {
+    if process.exe().is_some_and(|exe| path_is_inside(exe, dir)) {
+        return true;
+    }
+    if process.exe().is_some_and(is_launcher) {
+        return false;
+    }
+    process.cwd().is_some_and(|cwd| path_is_inside(cwd, dir)) || process.cmd().iter().any(|arg| path_is_inside(&unix_path_from_wine(&arg.to_string_lossy()), dir))
+}
+
+fn unix_path_from_wine(arg : &str) -> PathBuf { //Wine sees the Linux filesystem as drive Z:, so "Z:\home\me\game.exe" is "/home/me/game.exe". Anything else is left as it is
+    match arg.strip_prefix("Z:").or_else(|| arg.strip_prefix("z:")) {
+        Some(path) if !cfg!(windows) => PathBuf::from(path.replace('\\', "/")),
+        _ => PathBuf::from(arg),
+    }
+}
+
+//A running session as it was at its last checkpoint, read back on startup to count play time that never made it into a save
+pub struct Checkpoint {
+    pub app : AppId,
+    pub profile : ProfileId,
+    pub record : u64,
+    pub last_checkpoint : u64,
+    pub idle : u64, //Milliseconds before last_checkpoint that the user was idle
+}
+
+fn write_checkpoints(path : Option<&Path>, sessions : &[Session], now : u64) { //One line per session: app profile record checkpoint idle, where checkpoint is now and idle is the idle time since the session's last checkpoint
+    let Some(path) = path else { return };
+    let lines : Vec<String> = sessions.iter()
+        .filter(|session| session.has_started()) //Waiting sessions haven't played anything yet
+        .filter_map(|session| Some(format!("{} {} {} {} {}", session.app, session.profile, session.record?, now, session.idle_since_checkpoint))) //Sessions without a record yet have nothing to add time to
+        .collect();
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(path, lines.join("\n"));
+}
+
+/// Reads the sessions that were running when Catapult last stopped, and deletes the file so they're only counted once.
+pub fn read_checkpoints(path : &Path) -> Vec<Checkpoint> {
+    let Ok(contents) = fs::read_to_string(path) else { return Vec::new() };
+    let _ = fs::remove_file(path);
+    contents.lines().filter_map(|line| {
+        let mut numbers = line.split_whitespace().map(|number| number.parse::<u64>().ok());
+        Some(Checkpoint {
+            app : numbers.next()??,
+            profile : numbers.next()??,
+            record : numbers.next()??,
+            last_checkpoint : numbers.next()??,
+            idle : numbers.next().flatten().unwrap_or(0),
+        })
+    }).collect()
+}
+
+/// Looks through the running processes for apps in the library that aren't being tracked yet, e.g. games that were still running when Catapult closed or crashed, or that were started outside of Catapult. `sys` must have had its processes refreshed.
+fn find_untracked_apps(sys : &System, library : &Library, is_tracked : impl Fn(AppId) -> bool) -> Vec<Session> {
+    let mut found : Vec<Session> = Vec::new();
+    for entry in library.entries().iter().filter(|entry| !is_tracked(entry.id)) {
+        let process_name = normalize_process_name(&entry.tracked_process_name);
+        for (pid, process) in sys.processes() {
+            let profile = process.exe().and_then(|exe| matching_profile(entry, exe));
+            let profile = profile.or_else(|| (!process_name.is_empty() && normalize_process_name(&process.name().to_string_lossy()) == process_name).then(|| entry.default_profile_id()));
+            let profile = profile.or_else(|| (!entry.tracked_dir.is_empty() && is_in_dir(process, Path::new(&entry.tracked_dir))).then(|| entry.default_profile_id()));
+            if let Some(profile) = profile {
+                found.push(Session::new(entry.id, profile, pid.as_u32() as usize, &entry.tracked_process_name).with_tracked_dir(&entry.tracked_dir));
+                break; //The rest of the tree gets picked up by the tracker thread
+            }
+        }
+    }
+    found
+}
+
+fn matching_profile(entry : &AppEntry, exe : &Path) -> Option<ProfileId> { //Which profile runs this executable, checking the default first since several profiles can share one
+    let default_profile = entry.profile(entry.default_profile_index());
+    default_profile.into_iter().chain(entry.profiles.iter())
+        .find(|profile| {
+            let path = Path::new(entry.profile_path(profile));
+            !is_launcher(path) && paths_match(path, exe)
+        })
+        .map(|profile| profile.id)
+}
+
+fn is_launcher(path : &Path) -> bool { //"python3.11" and "wine64" count as "python" and "wine"
+    let name = normalize_process_name(&path.file_name().unwrap_or_default().to_string_lossy());
+    LAUNCHERS.contains(&name.trim_end_matches(|c : char| c.is_ascii_digit() || c == '.'))
+}
+
+fn path_is_inside(path : &Path, dir : &Path) -> bool { //Like paths_match, case and slashes don't matter on Windows. Steam often writes library paths in lower case
+    if cfg!(windows) {
+        let normalize = |path : &Path| path.to_string_lossy().to_lowercase().replace('/', "\\").trim_end_matches('\\').to_string();
+        let (path, dir) = (normalize(path), normalize(dir));
+        path == dir || path.starts_with(&format!("{}\\", dir))
+    } else {
+        path.starts_with(dir)
+    }
+}
+
+fn paths_match(a : &Path, b : &Path) -> bool {
+    if cfg!(windows) { //Windows paths aren't case sensitive and can use either slash
+        let normalize = |path : &Path| path.to_string_lossy().to_lowercase().replace('/', "\\");
+        normalize(a) == normalize(b)
+    } else {
+        a == b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_paths_inside_folders() {
+        assert!(path_is_inside(Path::new("/games/steamapps/common/Game/bin/game"), Path::new("/games/steamapps/common/Game")));
+        assert!(path_is_inside(Path::new("/games/steamapps/common/Game"), Path::new("/games/steamapps/common/Game/")));
+        assert!(!path_is_inside(Path::new("/games/steamapps/common/Game 2/game"), Path::new("/games/steamapps/common/Game")));
+        if cfg!(windows) {
+            assert!(path_is_inside(Path::new(r"C:\Program Files (x86)\Steam\steamapps\common\Game\game.exe"), Path::new("c:/program files (x86)/steam/steamapps/common/game")));
+        }
+    }
+
+    #[test]
+    fn converts_wine_paths() {
+        if !cfg!(windows) {
+            assert_eq!(unix_path_from_wine(r"Z:\home\me\Game\game.exe"), PathBuf::from("/home/me/Game/game.exe"));
+        }
+        assert_eq!(unix_path_from_wine(r"C:\Game\game.exe"), PathBuf::from(r"C:\Game\game.exe"));
+        assert_eq!(unix_path_from_wine("-windowed"), PathBuf::from("-windowed"));
+    }
+
+    #[test]
+    fn waiting_sessions_time_out_without_starting() {
+        let mut session = Session::waiting(1, 0, "game");
+        assert!(session.is_waiting(session.launch_deadline - 1));
+        assert!(!session.is_waiting(session.launch_deadline));
+        assert!(!session.has_started());
+        session.known_pids.insert((Pid::from(1), 0));
+        assert!(session.has_started());
+        assert!(!session.is_waiting(session.launch_deadline - 1));
+    }
+}